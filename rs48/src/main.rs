@@ -1,21 +1,47 @@
-use std::{fmt::Display, str::FromStr, time::Duration};
+use std::{
+	error::Error,
+	fmt::Display,
+	io, mem,
+	path::{Path, PathBuf},
+	process,
+	str::FromStr,
+	thread,
+	time::Duration,
+};
 
-use clap::Parser;
-use rs48_lib::prelude::*;
+use clap::{Parser, Subcommand};
+use rs48_lib::{
+	bench,
+	color_support::ColorMode,
+	grid_displayer::{GridDisplayer, ValueFormat},
+	prelude::*,
+	screen,
+};
+use signal_hook::{consts::signal::*, iterator::Signals};
 
 #[derive(Clone, Debug)]
 pub enum ControllerParam {
 	Player,
 	Random,
+	Greedy,
 	Simulated,
+	Expectimax,
+	Mcts,
+	NTuple,
+	Exec(String),
 }
 
 impl Display for ControllerParam {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		f.write_str(match *self {
+		f.write_str(match self {
 			ControllerParam::Player => "player",
 			ControllerParam::Random => "random",
+			ControllerParam::Greedy => "greedy",
 			ControllerParam::Simulated => "simulated",
+			ControllerParam::Expectimax => "expectimax",
+			ControllerParam::Mcts => "mcts",
+			ControllerParam::NTuple => "ntuple",
+			ControllerParam::Exec(command) => return write!(f, "exec:{command}"),
 		})
 	}
 }
@@ -27,8 +53,69 @@ impl FromStr for ControllerParam {
 		match s {
 			"player" => Ok(Self::Player),
 			"random" => Ok(Self::Random),
+			"greedy" => Ok(Self::Greedy),
 			"simulated" => Ok(Self::Simulated),
-			_ => Err(format!("failed to parse '{s}' into a 'ControllerParam'")),
+			"expectimax" => Ok(Self::Expectimax),
+			"mcts" => Ok(Self::Mcts),
+			"ntuple" => Ok(Self::NTuple),
+			_ => match s.strip_prefix("exec:") {
+				Some(command) => Ok(Self::Exec(command.to_string())),
+				None => Err(format!("failed to parse '{s}' into a 'ControllerParam'")),
+			},
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub enum RolloutParam {
+	Random,
+	Greedy,
+}
+
+impl Display for RolloutParam {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match *self {
+			RolloutParam::Random => "random",
+			RolloutParam::Greedy => "greedy",
+		})
+	}
+}
+
+impl FromStr for RolloutParam {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"random" => Ok(Self::Random),
+			"greedy" => Ok(Self::Greedy),
+			_ => Err(format!("failed to parse '{s}' into a 'RolloutParam'")),
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub enum RendererParam {
+	Ansi,
+	Plain,
+}
+
+impl Display for RendererParam {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match *self {
+			RendererParam::Ansi => "ansi",
+			RendererParam::Plain => "plain",
+		})
+	}
+}
+
+impl FromStr for RendererParam {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"ansi" => Ok(Self::Ansi),
+			"plain" => Ok(Self::Plain),
+			_ => Err(format!("failed to parse '{s}' into a 'RendererParam'")),
 		}
 	}
 }
@@ -37,57 +124,394 @@ impl FromStr for ControllerParam {
 #[derive(Parser)]
 #[clap(author, version, about)]
 pub struct Arguments {
+	#[clap(subcommand)]
+	command: Option<Command>,
+
 	/// size of the grid on which the game is played
-	#[clap(short, long, default_value_t = 4)]
+	#[clap(short, long, default_value_t = 4, global = true)]
 	size: usize,
 
+	/// number of columns of the grid, overrides the size
+	#[clap(long, global = true)]
+	width: Option<usize>,
+
+	/// number of rows of the grid, overrides the size
+	#[clap(long, global = true)]
+	height: Option<usize>,
+
 	/// number of tiles that will spawn on the grid each turn
-	#[clap(short = 'w', long, default_value_t = 1)]
+	#[clap(short = 'w', long, default_value_t = 1, global = true)]
 	spawn: usize,
 
-	/// disable clearing the terminal to refresh the screen
-	#[clap(long)]
+	/// values of the spawned tiles: 'classic' (2 at 90%, 4 at 10%), 'ones' or pairs like '2:9,4:1'
+	#[clap(long, default_value_t = SpawnTable::ones(), global = true)]
+	spawn_table: SpawnTable,
+
+	/// number of turns that can be undone in a row, 0 to disable undoing
+	#[clap(long, default_value_t = 100, global = true)]
+	undo_limit: usize,
+
+	/// points removed from the score for every undo
+	#[clap(long, default_value_t = 0, global = true)]
+	undo_penalty: usize,
+
+	/// let tiles merge more than once per move, `[1, 1, 2]` moved left gives `[4]`
+	#[clap(long, global = true)]
+	cascading: bool,
+
+	/// tile value that wins the game once reached, 0 for no victory,
+	/// by default the 2048 of the original game, 1024 when only ones spawn
+	#[clap(short, long, global = true)]
+	target: Option<usize>,
+
+	/// keep playing after reaching the target tile without asking
+	#[clap(long, global = true)]
+	keep_going: bool,
+
+	/// how the game is drawn: 'ansi' for colored boxes or 'plain' for ASCII without colors
+	#[clap(long, default_value_t = RendererParam::Ansi, global = true)]
+	renderer: RendererParam,
+
+	/// width of the tiles in characters, by default they fit the biggest value on the grid
+	#[clap(long, global = true)]
+	tile_width: Option<usize>,
+
+	/// how values are written on tiles: 'full', 'si' (131k), 'exponent' (2^17) or 'log2' (17)
+	#[clap(long, default_value_t = ValueFormat::Full, global = true)]
+	value_format: ValueFormat,
+
+	/// colors of the tiles: 'classic', 'dark', 'high-contrast', 'colorblind' or the path to a TOML theme file,
+	/// colors are derived from the color seed when none is given
+	#[clap(long, global = true)]
+	theme: Option<String>,
+
+	/// when to color the tiles: 'auto' detects what the terminal supports and respects NO_COLOR,
+	/// 'always' colors even when the output is not a terminal, 'never' disables colors
+	#[clap(long, default_value_t = ColorMode::Auto, global = true)]
+	color: ColorMode,
+
+	/// print every display after the previous one instead of refreshing the game in place on the alternate screen
+	#[clap(long, global = true)]
 	no_clear: bool,
 
 	/// skips the refresh of that many turns, allow AIs to play faster
-	#[clap(short = 'k', long, default_value_t = 0)]
+	#[clap(short = 'k', long, default_value_t = 0, global = true)]
 	display_skips: usize,
 
 	/// delay in ms to add between each turns
-	#[clap(short, long, default_value_t = 0)]
+	#[clap(short, long, default_value_t = 0, global = true)]
 	delay: u64,
 
-	/// the controller to use for the game
-	#[clap(short, long, default_value_t = ControllerParam::Player)]
+	/// the controller to use for the game, 'exec:<command>' plays the moves of a process,
+	/// the command is split on whitespace without a shell and the standard error of the process is discarded
+	#[clap(short, long, default_value_t = ControllerParam::Player, global = true)]
 	controller: ControllerParam,
 
+	/// seed of the tile spawns, the same seed and moves always give the same game
+	#[clap(long, global = true)]
+	seed: Option<u64>,
+
+	/// number of simulations per move of the simulated controller
+	#[clap(long, default_value_t = 100, global = true)]
+	simulations: usize,
+
+	/// number of moves of each simulation of the simulated controller
+	#[clap(long, default_value_t = 20, global = true)]
+	simulation_length: usize,
+
+	/// controller playing the simulations of the simulated controller: 'random' or 'greedy'
+	#[clap(long, default_value_t = RolloutParam::Random, global = true)]
+	rollout_policy: RolloutParam,
+
+	/// what the simulated controller maximises: 'score', 'survival' or 'max-tile'
+	#[clap(long, default_value_t = Objective::ScoreDelta, global = true)]
+	objective: Objective,
+
+	/// number of moves the expectimax controller looks ahead
+	#[clap(long, default_value_t = 2, global = true)]
+	depth: usize,
+
+	/// file of 'feature = weight' lines for the heuristic of the expectimax controller
+	#[clap(long, global = true)]
+	weights: Option<PathBuf>,
+
+	/// number of iterations per tree of the mcts controller
+	#[clap(long, default_value_t = 1000, global = true)]
+	iterations: usize,
+
+	/// time in ms the mcts controller searches for, replaces the iterations
+	#[clap(long, global = true)]
+	think_time: Option<u64>,
+
+	/// weights file of the ntuple controller, written by the train command
+	#[clap(long, global = true)]
+	network: Option<PathBuf>,
+
+	/// time in ms an 'exec:' controller has to answer each turn
+	#[clap(long, default_value_t = 5000, global = true)]
+	exec_timeout: u64,
+
+	/// file the game is recorded to once it ends, to watch it again with the replay command
+	#[clap(long, global = true)]
+	record: Option<PathBuf>,
+
+	/// file the game is written to when the player presses 's'
+	#[clap(long, default_value = "rs48-save.json", global = true)]
+	save_file: PathBuf,
+
+	/// resume a game written to a save file, its rules replace the ones given
+	#[clap(long, global = true)]
+	load: Option<PathBuf>,
+
 	/// sets a seed for the color pattern, 0 for random, default is 35
-	#[clap(long, default_value_t = 35)]
+	#[clap(long, default_value_t = 35, global = true)]
 	color_seed: u16,
 }
 
-fn main() -> Result<(), GameError> {
+#[derive(Subcommand)]
+pub enum Command {
+	/// train the network of the ntuple controller by playing against itself
+	Train {
+		/// number of games to play
+		#[clap(short, long, default_value_t = 10000)]
+		games: usize,
+
+		/// file the weights are written to, and read from first if it exists
+		#[clap(short, long)]
+		output: PathBuf,
+
+		/// step size of the updates of the weights
+		#[clap(long, default_value_t = 0.1)]
+		learning_rate: f32,
+	},
+	/// play games without display in parallel and report statistics about them
+	Bench {
+		/// number of games to play
+		#[clap(short, long, default_value_t = 100)]
+		games: usize,
+
+		/// print the statistics as JSON
+		#[clap(long)]
+		json: bool,
+	},
+	/// watch a recorded game, space pauses, arrows step and change the speed, 'q' quits
+	Replay {
+		/// file written by '--record'
+		file: PathBuf,
+
+		/// time in ms each turn is shown for
+		#[clap(long, default_value_t = 250)]
+		speed: u64,
+	},
+}
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
 	let arguments = Arguments::parse();
+	restore_terminal_on_signals()?;
 
-	let game_rules = GameRules::default()
-		.size(arguments.size)
-		.spawn_per_turn(arguments.spawn);
+	let width = arguments.width.unwrap_or(arguments.size);
+	let height = arguments.height.unwrap_or(arguments.size);
+	let mut game_rules = GameRules::default()
+		.width(width)
+		.height(height)
+		.spawn_per_turn(arguments.spawn)
+		.spawn_table(arguments.spawn_table.clone())
+		.seed(arguments.seed)
+		.merge_mode(if arguments.cascading {
+			MergeMode::Cascading
+		} else {
+			MergeMode::Standard
+		})
+		.keep_going(arguments.keep_going)
+		.undo_limit(Some(arguments.undo_limit))
+		.undo_penalty(arguments.undo_penalty);
+	if let Some(target) = arguments.target {
+		game_rules = game_rules.target_tile(Some(target).filter(|&target| target != 0));
+	}
 
 	let color_seed = seed_or_random(arguments.color_seed);
+	let theme = match &arguments.theme {
+		Some(name) => match Theme::built_in(name) {
+			Some(theme) => Some(theme),
+			None => Some(Theme::load(name)?),
+		},
+		None => None,
+	};
+	let color_support = arguments.color.support();
+	let grid_displayer = || {
+		GridDisplayer::new(color_seed)
+			.value_format(arguments.value_format)
+			.tile_length(arguments.tile_width)
+			.theme(theme.clone())
+			.color_support(color_support)
+	};
+	match &arguments.command {
+		Some(Command::Train {
+			games,
+			output,
+			learning_rate,
+		}) => {
+			let network = NTupleNetwork::new(width, height);
+			return train(network, &game_rules, *games, output, *learning_rate);
+		}
+		Some(Command::Bench { games, json }) => {
+			return bench(&arguments, &game_rules, *games, *json);
+		}
+		Some(Command::Replay { file, speed }) => {
+			let replay = Replay::load(file)?;
+			Playback::new(&replay, grid_displayer())
+				.delay(Duration::from_millis(*speed))
+				.run()?;
+			return Ok(());
+		}
+		None => (),
+	}
+
 	let manager_rules = ManagerRules::default()
+		.renderer(match arguments.renderer {
+			RendererParam::Ansi => AnsiRenderer::new(grid_displayer()).into_box(),
+			RendererParam::Plain => PlainRenderer.into_box(),
+		})
 		.clear_term(!arguments.no_clear)
 		.color_seed(color_seed)
 		.display_skips(arguments.display_skips)
-		.turn_duration(Duration::from_millis(arguments.delay));
+		.turn_duration(Duration::from_millis(arguments.delay))
+		.replay_file(arguments.record.clone())
+		.save_file(Some(arguments.save_file.clone()));
 
-	let controller = match arguments.controller {
-		ControllerParam::Player => PlayerController::default().into_box(),
-		ControllerParam::Random => RandomController::default().into_box(),
-		ControllerParam::Simulated => SimulatedController::new(100, 20).into_box(),
+	let game = match &arguments.load {
+		Some(path) => Game::load(path)?,
+		None => Game::new(game_rules),
 	};
-	let mut managed = GameManager::new(game_rules, manager_rules, controller);
+	let controller = controller(&arguments, game.get_rules())?;
+	let mut managed = GameManager::with_game(game, manager_rules, controller);
 
-	managed.play_all()
+	managed.play_all()?;
+	Ok(())
+}
+
+/// build the controller selected by the arguments, for games following the rules
+fn controller(
+	arguments: &Arguments,
+	game_rules: &GameRules,
+) -> Result<Box<dyn Controller>, Box<dyn Error + Send + Sync>> {
+	let controller = match &arguments.controller {
+		ControllerParam::Player => PlayerController.into_box(),
+		ControllerParam::Random => RandomController.into_box(),
+		ControllerParam::Greedy => GreedyController.into_box(),
+		ControllerParam::Simulated => {
+			let simulated =
+				SimulatedController::new(arguments.simulations, arguments.simulation_length)
+					.objective(arguments.objective);
+			match arguments.rollout_policy {
+				RolloutParam::Random => simulated,
+				RolloutParam::Greedy => simulated.rollout_policy(|| GreedyController.into_box()),
+			}
+			.into_box()
+		}
+		ControllerParam::Expectimax => {
+			let heuristic = match &arguments.weights {
+				Some(path) => WeightedHeuristic::load(path)?,
+				None => WeightedHeuristic::default(),
+			};
+			ExpectimaxController::new(arguments.depth)
+				.heuristic(heuristic)
+				.into_box()
+		}
+		ControllerParam::Mcts => {
+			let budget = match arguments.think_time {
+				Some(think_time) => Budget::Time(Duration::from_millis(think_time)),
+				None => Budget::Iterations(arguments.iterations),
+			};
+			MctsController::new(budget).into_box()
+		}
+		ControllerParam::NTuple => {
+			let path = arguments
+				.network
+				.as_ref()
+				.ok_or("the ntuple controller needs a weights file, see '--network'")?;
+			let (width, height) = (game_rules.get_width(), game_rules.get_height());
+			NTupleController::load(path, width, height)?.into_box()
+		}
+		ControllerParam::Exec(command) => ExternalController::spawn(command)?
+			.timeout(Duration::from_millis(arguments.exec_timeout))
+			.into_box(),
+	};
+	Ok(controller)
+}
+
+fn bench(
+	arguments: &Arguments,
+	game_rules: &GameRules,
+	games: usize,
+	json: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+	if let ControllerParam::Player = arguments.controller {
+		return Err("the player controller cannot play a bench".into());
+	}
+	let report = bench::run(game_rules, games, || controller(arguments, game_rules))?;
+	if json {
+		println!("{}", report.to_json());
+	} else {
+		print!("{report}");
+	}
+	Ok(())
+}
+
+fn train(
+	network: NTupleNetwork,
+	game_rules: &GameRules,
+	games: usize,
+	output: &Path,
+	learning_rate: f32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+	let mut network = if output.exists() {
+		NTupleNetwork::load(output)?
+	} else {
+		network
+	};
+
+	let report_every = 100;
+	let mut scores = Vec::with_capacity(report_every);
+	let mut biggest = 0;
+	network.train(game_rules, games, learning_rate, |index, game| {
+		scores.push(game.get_score());
+		biggest = biggest.max(game.get_board().biggest_value());
+		if scores.len() == report_every || index + 1 == games {
+			let mean = scores.iter().sum::<usize>() / scores.len();
+			println!(
+				"games: {:>8} | mean score: {mean:>8} | biggest tile: {biggest:>6}",
+				index + 1
+			);
+			scores.clear();
+			biggest = 0;
+		}
+	})?;
+
+	network.save(output)?;
+	println!("weights written to '{}'", output.display());
+	Ok(())
+}
+
+///
+/// on interrupts and terminations, put the terminal back like it was at startup and exit,
+/// the raw mode of players and the alternate screen would otherwise stay on it
+///
+fn restore_terminal_on_signals() -> io::Result<()> {
+	// the standard output is the terminal raw mode is set on
+	let mut attributes: libc::termios = unsafe { mem::zeroed() };
+	let is_terminal = unsafe { libc::tcgetattr(libc::STDOUT_FILENO, &mut attributes) } == 0;
+	let mut signals = Signals::new([SIGINT, SIGTERM])?;
+	thread::spawn(move || {
+		if let Some(signal) = signals.forever().next() {
+			if is_terminal {
+				unsafe { libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, &attributes) };
+			}
+			screen::restore_terminal();
+			process::exit(128 + signal);
+		}
+	});
+	Ok(())
 }
 
 fn seed_or_random(input: u16) -> u16 {
@@ -0,0 +1,193 @@
+use std::fmt::Display;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use serde_json::json;
+
+use crate::{
+	controller::Controller,
+	game::{self, GameError},
+	game_manager::{self, GameManager},
+};
+
+/// how one game of a bench ended
+#[derive(Debug, Clone, Copy)]
+pub struct GameResult {
+	pub score: usize,
+	pub turns: usize,
+	pub biggest_tile: usize,
+}
+
+///
+/// play games without display in parallel, each with a new controller,
+/// the games are seeded like a series, see [`Rules::nth_game`](game::Rules::nth_game),
+/// stops at the first error of a game or of the construction of a controller
+///
+pub fn run<E: From<GameError> + Send>(
+	rules: &game::Rules,
+	games: usize,
+	controller: impl Fn() -> Result<Box<dyn Controller>, E> + Send + Sync,
+) -> Result<Report, E> {
+	let results = (0..games)
+		.into_par_iter()
+		.map(|index| {
+			let manager_rules = game_manager::Rules::default().display(false);
+			let mut manager = GameManager::new(rules.nth_game(index), manager_rules, controller()?);
+			manager.play_all()?;
+			let game = manager.get_game();
+			Ok(GameResult {
+				score: game.get_score(),
+				turns: game.get_turn_index(),
+				biggest_tile: game.get_board().biggest_value(),
+			})
+		})
+		.collect::<Result<_, E>>()?;
+	let spawn_values = rules
+		.get_spawn_table()
+		.entries()
+		.iter()
+		.map(|&(value, _)| value)
+		.collect();
+	Ok(Report {
+		results,
+		spawn_values,
+	})
+}
+
+/// minimum, mean, median and maximum of a set of numbers
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+	pub min: usize,
+	pub mean: f64,
+	pub median: f64,
+	pub max: usize,
+}
+
+impl Summary {
+	fn new(mut values: Vec<usize>) -> Self {
+		values.sort_unstable();
+		let count = values.len();
+		if count == 0 {
+			return Self {
+				min: 0,
+				mean: 0.,
+				median: 0.,
+				max: 0,
+			};
+		}
+		let median = if count.is_multiple_of(2) {
+			(values[count / 2 - 1] + values[count / 2]) as f64 / 2.
+		} else {
+			values[count / 2] as f64
+		};
+		Self {
+			min: values[0],
+			mean: values.iter().sum::<usize>() as f64 / count as f64,
+			median,
+			max: values[count - 1],
+		}
+	}
+}
+
+///
+/// results of the games of a bench, displays as a table or converts to JSON
+///
+#[derive(Debug, Clone)]
+pub struct Report {
+	results: Vec<GameResult>,
+	/// values the games spawned, every other tile is one of them times a power of two
+	spawn_values: Vec<usize>,
+}
+
+impl Report {
+	pub fn results(&self) -> &[GameResult] {
+		&self.results
+	}
+
+	pub fn score(&self) -> Summary {
+		Summary::new(self.results.iter().map(|result| result.score).collect())
+	}
+
+	pub fn turns(&self) -> Summary {
+		Summary::new(self.results.iter().map(|result| result.turns).collect())
+	}
+
+	///
+	/// get every tile value up to the biggest one reached, in increasing order,
+	/// with the percentage of games whose biggest tile is at least that value
+	///
+	pub fn tiles_reached(&self) -> Vec<(usize, f64)> {
+		let biggest = self
+			.results
+			.iter()
+			.map(|result| result.biggest_tile)
+			.max()
+			.unwrap_or(0);
+		let mut tiles = vec![];
+		for &value in &self.spawn_values {
+			let mut tile = Some(value).filter(|&value| value > 0);
+			while let Some(value) = tile.filter(|&value| value <= biggest) {
+				tiles.push(value);
+				tile = value.checked_mul(2);
+			}
+		}
+		tiles.sort_unstable();
+		tiles.dedup();
+		tiles
+			.into_iter()
+			.map(|tile| {
+				let reached = self
+					.results
+					.iter()
+					.filter(|result| result.biggest_tile >= tile)
+					.count();
+				(tile, reached as f64 * 100. / self.results.len() as f64)
+			})
+			.collect()
+	}
+
+	pub fn to_json(&self) -> String {
+		let summary = |summary: Summary| {
+			json!({
+				"min": summary.min,
+				"mean": summary.mean,
+				"median": summary.median,
+				"max": summary.max,
+			})
+		};
+		let tiles: Vec<_> = self
+			.tiles_reached()
+			.into_iter()
+			.map(|(tile, percentage)| json!({ "tile": tile, "percentage": percentage }))
+			.collect();
+		json!({
+			"games": self.results.len(),
+			"score": summary(self.score()),
+			"turns": summary(self.turns()),
+			"tiles_reached": tiles,
+		})
+		.to_string()
+	}
+}
+
+impl Display for Report {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "games: {}", self.results.len())?;
+		writeln!(
+			f,
+			"{:<8}{:>12}{:>12}{:>12}{:>12}",
+			"", "min", "mean", "median", "max"
+		)?;
+		for (name, summary) in [("score", self.score()), ("turns", self.turns())] {
+			writeln!(
+				f,
+				"{name:<8}{:>12}{:>12.1}{:>12.1}{:>12}",
+				summary.min, summary.mean, summary.median, summary.max
+			)?;
+		}
+		writeln!(f, "tiles reached:")?;
+		for (tile, percentage) in self.tiles_reached() {
+			writeln!(f, "{tile:>12}{percentage:>11.1}%")?;
+		}
+		Ok(())
+	}
+}
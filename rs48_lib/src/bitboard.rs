@@ -0,0 +1,239 @@
+use std::sync::OnceLock;
+
+use crate::controller::Move;
+
+///
+/// a 4×4 grid packed in a single `u64`, each cell is a 4 bits exponent,
+/// 0 is an empty cell and `n` a tile of value `unit * 2^(n - 1)`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitBoard {
+	bits: u64,
+	unit: usize,
+}
+
+impl BitBoard {
+	/// width and height of the packed grid
+	pub const SIZE: usize = 4;
+
+	/// biggest exponent that fits in a cell
+	pub const MAX_EXPONENT: u8 = 15;
+
+	///
+	/// constructor, `unit` is the value of the smallest tile
+	///
+	pub fn new(unit: usize) -> Self {
+		Self { bits: 0, unit }
+	}
+
+	///
+	/// get the raw packed cells
+	///
+	pub fn bits(&self) -> u64 {
+		self.bits
+	}
+
+	///
+	/// get the value of the smallest tile
+	///
+	pub fn unit(&self) -> usize {
+		self.unit
+	}
+
+	///
+	/// get the exponent of the cell at the selected position
+	///
+	pub fn exponent(&self, (x, y): (usize, usize)) -> u8 {
+		((self.bits >> Self::shift((x, y))) & 0xF) as u8
+	}
+
+	///
+	/// set the exponent of the cell at the selected position
+	///
+	pub fn set_exponent(&mut self, (x, y): (usize, usize), exponent: u8) {
+		let shift = Self::shift((x, y));
+		self.bits = (self.bits & !(0xF << shift)) | ((exponent as u64 & 0xF) << shift);
+	}
+
+	///
+	/// get the value of the tile at the selected position if there is one
+	///
+	pub fn get(&self, (x, y): (usize, usize)) -> Option<usize> {
+		match self.exponent((x, y)) {
+			0 => None,
+			exponent => Some(self.unit << (exponent - 1)),
+		}
+	}
+
+	///
+	/// get the exponent representing a value, if it can be represented
+	///
+	pub fn encode(&self, value: Option<usize>) -> Option<u8> {
+		let value = match value {
+			None => return Some(0),
+			Some(value) => value,
+		};
+		if !value.is_multiple_of(self.unit) || !(value / self.unit).is_power_of_two() {
+			return None;
+		}
+		let exponent = (value / self.unit).trailing_zeros() + 1;
+		if exponent > Self::MAX_EXPONENT as u32 {
+			None
+		} else {
+			Some(exponent as u8)
+		}
+	}
+
+	///
+	/// wether a move could merge two tiles into a value that does not fit in a cell
+	///
+	pub fn is_saturated(&self) -> bool {
+		(0..Self::SIZE * Self::SIZE)
+			.any(|index| (self.bits >> (index * 4)) & 0xF == Self::MAX_EXPONENT as u64)
+	}
+
+	///
+	/// perform a move with merges happening at most once per tile, return the score of the move
+	///
+	pub fn perform_move(&mut self, movement: Move) -> usize {
+		let tables = tables();
+		let (bits, score) = match movement {
+			Move::LEFT => Self::move_rows(self.bits, &tables.left, &tables.left_score),
+			Move::RIGHT => Self::move_rows(self.bits, &tables.right, &tables.right_score),
+			Move::UP => {
+				let (bits, score) =
+					Self::move_rows(transpose(self.bits), &tables.left, &tables.left_score);
+				(transpose(bits), score)
+			}
+			Move::DOWN => {
+				let (bits, score) =
+					Self::move_rows(transpose(self.bits), &tables.right, &tables.right_score);
+				(transpose(bits), score)
+			}
+		};
+		self.bits = bits;
+		score as usize * self.unit
+	}
+
+	fn move_rows(bits: u64, rows: &[u16], scores: &[u32]) -> (u64, u32) {
+		let mut result = 0;
+		let mut score = 0;
+		for y in 0..Self::SIZE {
+			let row = ((bits >> (y * 16)) & 0xFFFF) as usize;
+			result |= (rows[row] as u64) << (y * 16);
+			score += scores[row];
+		}
+		(result, score)
+	}
+
+	fn shift((x, y): (usize, usize)) -> usize {
+		(y * Self::SIZE + x) * 4
+	}
+}
+
+/// swap rows and columns of the packed grid
+fn transpose(bits: u64) -> u64 {
+	let a1 = bits & 0xF0F0_0F0F_F0F0_0F0F;
+	let a2 = bits & 0x0000_F0F0_0000_F0F0;
+	let a3 = bits & 0x0F0F_0000_0F0F_0000;
+	let a = a1 | (a2 << 12) | (a3 >> 12);
+	let b1 = a & 0xFF00_FF00_00FF_00FF;
+	let b2 = a & 0x00FF_00FF_0000_0000;
+	let b3 = a & 0x0000_0000_FF00_FF00;
+	b1 | (b2 >> 24) | (b3 << 24)
+}
+
+/// result and score, in units, of moving every possible row left or right
+struct Tables {
+	left: Vec<u16>,
+	left_score: Vec<u32>,
+	right: Vec<u16>,
+	right_score: Vec<u32>,
+}
+
+fn tables() -> &'static Tables {
+	static TABLES: OnceLock<Tables> = OnceLock::new();
+	TABLES.get_or_init(|| {
+		let (left, left_score): (Vec<_>, Vec<_>) = (0..=u16::MAX).map(slide_row_left).unzip();
+		let (right, right_score) = (0..=u16::MAX)
+			.map(|row| {
+				let (result, score) = slide_row_left(reverse_row(row));
+				(reverse_row(result), score)
+			})
+			.unzip();
+		Tables {
+			left,
+			left_score,
+			right,
+			right_score,
+		}
+	})
+}
+
+/// slide a row towards its first cell, merging each tile at most once
+fn slide_row_left(row: u16) -> (u16, u32) {
+	let mut result = [0u8; BitBoard::SIZE];
+	let mut score = 0;
+	let mut length = 0;
+	let mut can_merge = false;
+	for exponent in (0..BitBoard::SIZE).map(|x| ((row >> (x * 4)) & 0xF) as u8) {
+		if exponent == 0 {
+			continue;
+		}
+		if can_merge && result[length - 1] == exponent {
+			// clamped, moves are never played on saturated grids, see [`BitBoard::is_saturated`]
+			result[length - 1] = (exponent + 1).min(BitBoard::MAX_EXPONENT);
+			score += 1 << exponent;
+			can_merge = false;
+		} else {
+			result[length] = exponent;
+			length += 1;
+			can_merge = true;
+		}
+	}
+	let row = result
+		.iter()
+		.enumerate()
+		.fold(0, |row, (x, &exponent)| row | (exponent as u16) << (x * 4));
+	(row, score)
+}
+
+fn reverse_row(row: u16) -> u16 {
+	(row >> 12) | ((row >> 4) & 0x00F0) | ((row << 4) & 0x0F00) | (row << 12)
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{Rng, SeedableRng};
+	use rand_chacha::ChaCha8Rng;
+
+	use super::BitBoard;
+	use crate::{
+		controller::Move,
+		game::{Game, MergeMode},
+		grid::Grid,
+	};
+
+	#[test]
+	fn moves_match_the_generic_grid() {
+		let mut rng = ChaCha8Rng::seed_from_u64(0);
+		for _ in 0..10_000 {
+			let unit = [1, 2, 3][rng.gen_range(0..3)];
+			let mut packed = Grid::new_packed(unit);
+			// below the saturated exponent
+			packed.fill_randomly(&mut rng, unit, BitBoard::MAX_EXPONENT as usize - 2);
+			let mut rows = Grid::new(BitBoard::SIZE, BitBoard::SIZE);
+			for (position, value) in packed.positions().zip(packed.values()) {
+				rows.set(position, value);
+			}
+			assert!(packed.packed_mut().is_some());
+			for movement in Move::all() {
+				let (mut packed, mut rows) = (packed.clone(), rows.clone());
+				let packed_score = Game::move_board(&mut packed, movement, MergeMode::Standard);
+				let rows_score = Game::move_board(&mut rows, movement, MergeMode::Standard);
+				assert_eq!(packed_score, rows_score, "score of {movement:?}");
+				assert_eq!(packed.values(), rows.values(), "tiles after {movement:?}");
+			}
+		}
+	}
+}
@@ -0,0 +1,188 @@
+use std::{env, fmt::Display, io::stdout, str::FromStr};
+
+use termion::color;
+
+/// colors a terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+	/// no color at all, only text
+	None,
+	/// the 16 colors of the terminal palette
+	Ansi16,
+	/// the 256 colors of xterm
+	Ansi256,
+	/// any 24-bit color
+	TrueColor,
+}
+
+impl ColorSupport {
+	///
+	/// guess what the terminal on the standard output supports, from the `NO_COLOR`,
+	/// `COLORTERM` and `TERM` environment variables, there are no colors when the
+	/// output is not a terminal
+	///
+	pub fn detect() -> Self {
+		let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+		if no_color || !termion::is_tty(&stdout()) {
+			return Self::None;
+		}
+		Self::from_env()
+	}
+
+	/// the depth announced by `COLORTERM` and `TERM`, ignoring `NO_COLOR`
+	fn from_env() -> Self {
+		let colorterm = env::var("COLORTERM").unwrap_or_default();
+		let term = env::var("TERM").unwrap_or_default();
+		if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+			Self::TrueColor
+		} else if term.contains("256color") {
+			Self::Ansi256
+		} else if term.is_empty() || term == "dumb" {
+			Self::None
+		} else {
+			Self::Ansi16
+		}
+	}
+
+	///
+	/// escape code setting the background to the closest available color
+	///
+	pub fn background(&self, rgb: (u8, u8, u8)) -> String {
+		match self {
+			Self::None => String::new(),
+			Self::Ansi16 => format!("\x1B[{}m", ansi_16_code(nearest_ansi_16(rgb)) + 10),
+			Self::Ansi256 => color::Bg(color::AnsiValue(nearest_ansi_256(rgb))).to_string(),
+			Self::TrueColor => color::Bg(color::Rgb(rgb.0, rgb.1, rgb.2)).to_string(),
+		}
+	}
+
+	///
+	/// escape code setting the foreground to the closest available color
+	///
+	pub fn foreground(&self, rgb: (u8, u8, u8)) -> String {
+		match self {
+			Self::None => String::new(),
+			Self::Ansi16 => format!("\x1B[{}m", ansi_16_code(nearest_ansi_16(rgb))),
+			Self::Ansi256 => color::Fg(color::AnsiValue(nearest_ansi_256(rgb))).to_string(),
+			Self::TrueColor => color::Fg(color::Rgb(rgb.0, rgb.1, rgb.2)).to_string(),
+		}
+	}
+
+	/// escape code going back to the default colors
+	pub fn reset(&self) -> String {
+		match self {
+			Self::None => String::new(),
+			_ => format!("{}{}", color::Bg(color::Reset), color::Fg(color::Reset)),
+		}
+	}
+}
+
+/// when to use colors, the `--color` option of most command line tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+	/// colors supported by the terminal, none when `NO_COLOR` is set or the output is not a terminal
+	Auto,
+	/// colors even when the output is not a terminal, at least the 16 colors of the palette
+	Always,
+	/// no colors
+	Never,
+}
+
+impl ColorMode {
+	pub fn support(&self) -> ColorSupport {
+		match self {
+			Self::Auto => ColorSupport::detect(),
+			Self::Always => ColorSupport::from_env().max(ColorSupport::Ansi16),
+			Self::Never => ColorSupport::None,
+		}
+	}
+}
+
+impl Display for ColorMode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::Auto => "auto",
+			Self::Always => "always",
+			Self::Never => "never",
+		})
+	}
+}
+
+impl FromStr for ColorMode {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"auto" => Ok(Self::Auto),
+			"always" => Ok(Self::Always),
+			"never" => Ok(Self::Never),
+			_ => Err(format!("failed to parse '{s}' into a 'ColorMode'")),
+		}
+	}
+}
+
+/// usual values of the 16 palette colors, terminals are free to change them
+const ANSI_16_RGB: [(u8, u8, u8); 16] = [
+	(0, 0, 0),
+	(205, 0, 0),
+	(0, 205, 0),
+	(205, 205, 0),
+	(0, 0, 238),
+	(205, 0, 205),
+	(0, 205, 205),
+	(229, 229, 229),
+	(127, 127, 127),
+	(255, 0, 0),
+	(0, 255, 0),
+	(255, 255, 0),
+	(92, 92, 255),
+	(255, 0, 255),
+	(0, 255, 255),
+	(255, 255, 255),
+];
+
+/// levels of each component in the 6×6×6 cube of the 256 colors
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+	let component = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+	component(a.0, b.0) + component(a.1, b.1) + component(a.2, b.2)
+}
+
+/// foreground code of a palette color, the background one is 10 more, termion writes
+/// them as 256 colors which terminals with only 16 do not understand
+fn ansi_16_code(index: usize) -> usize {
+	if index < 8 {
+		30 + index
+	} else {
+		90 + index - 8
+	}
+}
+
+fn nearest_ansi_16(rgb: (u8, u8, u8)) -> usize {
+	(0..ANSI_16_RGB.len())
+		.min_by_key(|&index| distance(rgb, ANSI_16_RGB[index]))
+		.expect("the palette is not empty")
+}
+
+/// the closest of the color cube and the gray ramp, the first 16 colors are left out as they vary between terminals
+fn nearest_ansi_256(rgb: (u8, u8, u8)) -> u8 {
+	let level = |component: u8| {
+		(0..CUBE_LEVELS.len())
+			.min_by_key(|&index| (CUBE_LEVELS[index] as i32 - component as i32).abs())
+			.expect("there are levels")
+	};
+	let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+	let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+	let cube_index = 16 + 36 * r + 6 * g + b;
+
+	let average = (rgb.0 as usize + rgb.1 as usize + rgb.2 as usize) / 3;
+	let gray_step = (average.saturating_sub(3) / 10).min(23);
+	let gray_level = (8 + 10 * gray_step) as u8;
+	let gray = (gray_level, gray_level, gray_level);
+	if distance(rgb, gray) < distance(rgb, cube) {
+		(232 + gray_step) as u8
+	} else {
+		cube_index as u8
+	}
+}
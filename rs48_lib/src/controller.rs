@@ -1,9 +1,11 @@
 use rand::{distributions::Standard, prelude::Distribution};
+use serde::{Deserialize, Serialize};
 
 use crate::game::Game;
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, str::FromStr, time::Duration};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Move {
 	LEFT,
 	RIGHT,
@@ -17,6 +19,31 @@ impl Move {
 	}
 }
 
+impl Display for Move {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::LEFT => "left",
+			Self::RIGHT => "right",
+			Self::UP => "up",
+			Self::DOWN => "down",
+		})
+	}
+}
+
+impl FromStr for Move {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"left" => Ok(Self::LEFT),
+			"right" => Ok(Self::RIGHT),
+			"up" => Ok(Self::UP),
+			"down" => Ok(Self::DOWN),
+			_ => Err(format!("failed to parse '{s}' into a 'Move'")),
+		}
+	}
+}
+
 impl Distribution<Move> for Standard {
 	fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Move {
 		match rng.gen_range(0..4) {
@@ -28,25 +55,75 @@ impl Distribution<Move> for Standard {
 	}
 }
 
+/// what a controller wants to do on its turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+	Move(Move),
+	Undo,
+	Redo,
+	/// write the game to the save file of the manager
+	Save,
+}
+
 #[derive(Debug)]
 pub enum ControllerError {
 	ExitSignal,
+	/// an external controller exited or its pipes broke
+	ProcessCrashed(String),
+	/// an external controller did not answer in time
+	Timeout(Duration),
+	/// an external controller answered something that is not a legal move
+	InvalidResponse(String),
 }
 
 impl Display for ControllerError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		let msg = match self {
-			ControllerError::ExitSignal => "received exit signal",
-		};
-		f.write_str(msg)
+		match self {
+			ControllerError::ExitSignal => f.write_str("received exit signal"),
+			ControllerError::ProcessCrashed(reason) => {
+				write!(f, "controller process crashed: {reason}")
+			}
+			ControllerError::Timeout(duration) => {
+				write!(f, "controller process did not answer within {duration:?}")
+			}
+			ControllerError::InvalidResponse(response) => {
+				write!(
+					f,
+					"controller process answered '{response}' instead of a legal move"
+				)
+			}
+		}
 	}
 }
 
 impl Error for ControllerError {}
 
+///
+/// move played when there is nothing to decide, the first legal one or any move once the game is lost,
+/// the manager never asks for a move on a lost game
+///
+pub fn fallback_move(game: &Game) -> Move {
+	game.legal_moves().first().copied().unwrap_or(Move::LEFT)
+}
+
 pub trait Controller {
 	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError>;
 
+	/// next action to perform, controllers that navigate the history override this
+	fn next_action(&mut self, game: &Game) -> Result<Action, ControllerError> {
+		self.next_move(game).map(Action::Move)
+	}
+
+	/// wether to keep playing once the target tile is reached, automated controllers always do
+	fn keep_going(&mut self, _game: &Game) -> Result<bool, ControllerError> {
+		Ok(true)
+	}
+
+	/// wether a person plays through this controller, only they are asked again after a refused move
+	fn interactive(&self) -> bool {
+		false
+	}
+
 	fn into_box(self) -> Box<dyn Controller>
 	where
 		Self: Sized + 'static,
@@ -55,10 +132,20 @@ pub trait Controller {
 	}
 }
 
+pub mod expectimax;
+pub mod external;
+pub mod greedy;
+pub mod mcts;
+pub mod ntuple;
 pub mod player;
 pub mod random;
 pub mod simulated;
 
+pub use expectimax::ExpectimaxController;
+pub use external::ExternalController;
+pub use greedy::GreedyController;
+pub use mcts::MctsController;
+pub use ntuple::NTupleController;
 pub use player::PlayerController;
 pub use random::RandomController;
 pub use simulated::SimulatedController;
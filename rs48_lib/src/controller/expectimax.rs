@@ -0,0 +1,115 @@
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+	game::{Game, MergeMode, SpawnTable},
+	grid::Grid,
+	heuristic::{Heuristic, WeightedHeuristic},
+};
+
+use super::{fallback_move, Controller, ControllerError, Move};
+
+///
+/// searches every move and every spawn that can follow it, down to a given depth,
+/// and plays the move with the best expected evaluation of the resulting grids
+///
+pub struct ExpectimaxController {
+	depth: usize,
+	heuristic: Box<dyn Heuristic>,
+}
+
+impl ExpectimaxController {
+	///
+	/// constructor, `depth` is the number of moves to look ahead
+	///
+	pub fn new(depth: usize) -> Self {
+		Self {
+			depth,
+			heuristic: WeightedHeuristic::default().into_box(),
+		}
+	}
+
+	///
+	/// set the evaluation of the grids at the end of the search, higher is better
+	///
+	pub fn heuristic(mut self, heuristic: impl Heuristic + 'static) -> Self {
+		self.heuristic = heuristic.into_box();
+		self
+	}
+}
+
+impl Controller for ExpectimaxController {
+	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
+		let search = Search {
+			heuristic: &*self.heuristic,
+			spawn_table: game.get_spawn_table(),
+			merge_mode: game.get_merge_mode(),
+		};
+		let board = game.get_board();
+
+		let mut values: Vec<_> = game
+			.legal_moves()
+			.into_par_iter()
+			.map(|movement| {
+				let mut after = board.clone();
+				Game::move_board(&mut after, movement, search.merge_mode);
+				(
+					movement,
+					search.chance(&after, self.depth.saturating_sub(1)),
+				)
+			})
+			.collect();
+		values.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+		let movement = values
+			.first()
+			.map_or_else(|| fallback_move(game), |&(m, _)| m);
+		Ok(movement)
+	}
+}
+
+struct Search<'s> {
+	heuristic: &'s dyn Heuristic,
+	spawn_table: &'s SpawnTable,
+	merge_mode: MergeMode,
+}
+
+impl<'s> Search<'s> {
+	/// best value among the moves of the player
+	fn max(&self, grid: &Grid, depth: usize) -> f64 {
+		if depth == 0 {
+			return self.heuristic.evaluate(grid);
+		}
+		Move::all()
+			.into_iter()
+			.filter_map(|movement| {
+				let mut after = grid.clone();
+				Game::move_board(&mut after, movement, self.merge_mode);
+				(after != *grid).then(|| self.chance(&after, depth - 1))
+			})
+			.reduce(f64::max)
+			// the game is lost, the grid is evaluated as is
+			.unwrap_or_else(|| self.heuristic.evaluate(grid))
+	}
+
+	/// average value over every spawn that can follow a move, weighted by its odds
+	fn chance(&self, grid: &Grid, depth: usize) -> f64 {
+		let empty_cells: Vec<_> = grid
+			.positions()
+			.filter(|&position| grid.get_val(position).is_none())
+			.collect();
+		if empty_cells.is_empty() {
+			return self.max(grid, depth);
+		}
+		let total_weight = self.spawn_table.total_weight();
+		let mut expected = 0.;
+		for &position in &empty_cells {
+			for &(value, weight) in self.spawn_table.entries() {
+				let mut spawned = grid.clone();
+				spawned.set(position, Some(value));
+				let odds = weight as f64 / total_weight as f64 / empty_cells.len() as f64;
+				expected += odds * self.max(&spawned, depth);
+			}
+		}
+		expected
+	}
+}
@@ -0,0 +1,140 @@
+use std::{
+	io::{self, BufRead, BufReader, Write},
+	process::{Child, ChildStdin, Command, Stdio},
+	sync::mpsc::{self, Receiver, RecvTimeoutError},
+	thread,
+	time::Duration,
+};
+
+use serde_json::json;
+
+use crate::game::Game;
+
+use super::{Controller, ControllerError, Move};
+
+///
+/// plays the moves of a child process, each turn the process receives one line of JSON
+/// `{"board": [[0, 2, ...], ...], "score": 0, "turn": 0, "legal_moves": ["left", ...]}`
+/// with rows of tile values, 0 for empty cells, and answers one line with the name of a legal move,
+/// the process is killed when it does not answer in time, what it writes to its standard error
+/// is discarded as it would be drawn over the game
+///
+pub struct ExternalController {
+	child: Child,
+	stdin: ChildStdin,
+	lines: Receiver<io::Result<String>>,
+	timeout: Duration,
+}
+
+impl ExternalController {
+	///
+	/// constructor starting the process of a command, its program followed by whitespace separated arguments,
+	/// neither of them can contain whitespace as the command is not run through a shell
+	///
+	pub fn spawn(command: &str) -> io::Result<Self> {
+		let mut words = command.split_whitespace();
+		let program = words
+			.next()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty command"))?;
+		let mut child = Command::new(program)
+			.args(words)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::null())
+			.spawn()?;
+		let stdin = child.stdin.take().expect("stdin is piped");
+		let stdout = child.stdout.take().expect("stdout is piped");
+
+		// reading happens on its own thread so that waiting for an answer can time out
+		let (sender, lines) = mpsc::channel();
+		thread::spawn(move || {
+			for line in BufReader::new(stdout).lines() {
+				if sender.send(line).is_err() {
+					break;
+				}
+			}
+		});
+
+		Ok(Self {
+			child,
+			stdin,
+			lines,
+			timeout: Duration::from_secs(5),
+		})
+	}
+
+	/// time the process has to answer each turn
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	fn send(&mut self, game: &Game) -> io::Result<()> {
+		let board = game.get_board();
+		let rows: Vec<Vec<_>> = (0..board.height())
+			.map(|y| {
+				(0..board.width())
+					.map(|x| board.get_val((x, y)).unwrap_or(0))
+					.collect()
+			})
+			.collect();
+		let legal_moves: Vec<_> = game
+			.legal_moves()
+			.into_iter()
+			.map(|movement| movement.to_string())
+			.collect();
+		let message = json!({
+			"board": rows,
+			"score": game.get_score(),
+			"turn": game.get_turn_index(),
+			"legal_moves": legal_moves,
+		});
+		writeln!(self.stdin, "{message}")?;
+		self.stdin.flush()
+	}
+
+	/// error describing why the process stopped answering
+	fn crashed(&mut self, reason: impl ToString) -> ControllerError {
+		let reason = match self.child.try_wait() {
+			Ok(Some(status)) => status.to_string(),
+			_ => reason.to_string(),
+		};
+		ControllerError::ProcessCrashed(reason)
+	}
+}
+
+impl Controller for ExternalController {
+	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
+		if let Err(error) = self.send(game) {
+			return Err(self.crashed(error));
+		}
+		let line = match self.lines.recv_timeout(self.timeout) {
+			Ok(Ok(line)) => line,
+			Ok(Err(error)) => return Err(self.crashed(error)),
+			Err(RecvTimeoutError::Timeout) => {
+				// a late answer would be taken for the answer to the next turn
+				self.child.kill().ok();
+				self.child.wait().ok();
+				return Err(ControllerError::Timeout(self.timeout));
+			}
+			Err(RecvTimeoutError::Disconnected) => {
+				return Err(self.crashed("output closed"));
+			}
+		};
+		let response = line.trim();
+		let invalid = || ControllerError::InvalidResponse(response.to_string());
+		let movement: Move = response.to_lowercase().parse().map_err(|_| invalid())?;
+		// refused moves would be asked again forever
+		if !game.legal_moves().contains(&movement) {
+			return Err(invalid());
+		}
+		Ok(movement)
+	}
+}
+
+impl Drop for ExternalController {
+	fn drop(&mut self) {
+		self.child.kill().ok();
+		self.child.wait().ok();
+	}
+}
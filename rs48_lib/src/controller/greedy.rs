@@ -0,0 +1,25 @@
+use rand::{seq::SliceRandom, thread_rng};
+
+use super::{fallback_move, Controller, ControllerError, Move};
+use crate::game::Game;
+
+///
+/// plays the legal move that scores the most right away, picking randomly among ties
+///
+#[derive(Debug, Default)]
+pub struct GreedyController;
+
+impl Controller for GreedyController {
+	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
+		let mut moves = game.legal_moves();
+		moves.shuffle(&mut thread_rng());
+		let movement = moves
+			.into_iter()
+			.max_by_key(|&movement| {
+				let mut board = game.get_board().clone();
+				Game::move_board(&mut board, movement, game.get_merge_mode())
+			})
+			.unwrap_or_else(|| fallback_move(game));
+		Ok(movement)
+	}
+}
@@ -0,0 +1,300 @@
+use std::time::{Duration, Instant};
+
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{
+	game::{Game, MergeMode, SpawnTable},
+	grid::Grid,
+};
+
+use super::{fallback_move, Controller, ControllerError, Move};
+
+/// how long the search runs before each move
+#[derive(Debug, Clone, Copy)]
+pub enum Budget {
+	/// number of iterations per tree, at least one
+	Iterations(usize),
+	/// time spent searching, at least one iteration long
+	Time(Duration),
+}
+
+///
+/// Monte Carlo tree search with UCT selection, grows one tree per rayon thread,
+/// and keeps the part of the trees that follows the move that was played
+///
+pub struct MctsController {
+	budget: Budget,
+	exploration: f64,
+	rollout_length: usize,
+	trees: Vec<Decision>,
+}
+
+impl MctsController {
+	pub fn new(budget: Budget) -> Self {
+		Self {
+			budget,
+			exploration: 1.4,
+			rollout_length: 20,
+			trees: vec![],
+		}
+	}
+
+	/// weight of the exploration term of UCT
+	pub fn exploration(mut self, exploration: f64) -> Self {
+		self.exploration = exploration;
+		self
+	}
+
+	/// number of random moves played from a new node to evaluate it
+	pub fn rollout_length(mut self, rollout_length: usize) -> Self {
+		self.rollout_length = rollout_length;
+		self
+	}
+
+	/// replace every tree by the subtree starting at the grid, or a new one when it was not explored
+	fn reuse_trees(&mut self, board: &Grid) {
+		let tree_count = rayon::current_num_threads();
+		let mut trees: Vec<_> = self
+			.trees
+			.drain(..)
+			.filter_map(|tree| tree.into_subtree(board))
+			.take(tree_count)
+			.collect();
+		let missing = tree_count - trees.len();
+		trees.extend((0..missing).map(|_| Decision::new(board.clone())));
+		self.trees = trees;
+	}
+}
+
+impl Controller for MctsController {
+	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
+		self.reuse_trees(game.get_board());
+		let search = Search {
+			spawn_table: game.get_spawn_table(),
+			merge_mode: game.get_merge_mode(),
+			exploration: self.exploration,
+			rollout_length: self.rollout_length,
+		};
+
+		let budget = self.budget;
+		let start = Instant::now();
+		self.trees.par_iter_mut().for_each(|tree| {
+			let mut rng = thread_rng();
+			let mut iterations = 0;
+			loop {
+				let done = match budget {
+					// at least one iteration, so that the search always finds a move
+					Budget::Iterations(count) => iterations > 0 && iterations >= count,
+					Budget::Time(duration) => iterations > 0 && start.elapsed() >= duration,
+				};
+				if done {
+					break;
+				}
+				search.iterate(tree, &mut rng);
+				iterations += 1;
+			}
+		});
+
+		// the most visited move over all trees
+		let movement = Move::all()
+			.into_iter()
+			.map(|movement| {
+				let visits: usize = self
+					.trees
+					.iter()
+					.filter_map(|tree| tree.child(movement))
+					.map(|chance| chance.visits)
+					.sum();
+				(movement, visits)
+			})
+			.filter(|&(_, visits)| visits > 0)
+			.max_by_key(|&(_, visits)| visits)
+			.map(|(movement, _)| movement);
+		let movement = movement.unwrap_or_else(|| fallback_move(game));
+		Ok(movement)
+	}
+}
+
+/// a grid on which the player has to choose a move
+struct Decision {
+	grid: Grid,
+	visits: usize,
+	total: f64,
+	children: Option<Vec<(Move, Chance)>>,
+}
+
+/// a grid after a move, waiting for a spawn
+struct Chance {
+	grid: Grid,
+	score: usize,
+	visits: usize,
+	total: f64,
+	children: Vec<Decision>,
+}
+
+impl Decision {
+	fn new(grid: Grid) -> Self {
+		Self {
+			grid,
+			visits: 0,
+			total: 0.,
+			children: None,
+		}
+	}
+
+	fn child(&self, movement: Move) -> Option<&Chance> {
+		let children = self.children.as_ref()?;
+		children
+			.iter()
+			.find(|(child_move, _)| *child_move == movement)
+			.map(|(_, chance)| chance)
+	}
+
+	/// the node of the grid reached after one move and its spawns, if it was explored
+	fn into_subtree(self, grid: &Grid) -> Option<Decision> {
+		self.children?
+			.into_iter()
+			.flat_map(|(_, chance)| chance.children)
+			.find(|decision| decision.grid == *grid)
+	}
+}
+
+struct Search<'s> {
+	spawn_table: &'s SpawnTable,
+	merge_mode: MergeMode,
+	exploration: f64,
+	rollout_length: usize,
+}
+
+impl<'s> Search<'s> {
+	/// select down the tree, expand a node and back up the reward of its rollout
+	fn iterate(&self, decision: &mut Decision, rng: &mut impl Rng) -> f64 {
+		let merge_mode = self.merge_mode;
+		let grid = &decision.grid;
+		let children = decision.children.get_or_insert_with(|| {
+			Move::all()
+				.into_iter()
+				.filter_map(|movement| {
+					let mut after = grid.clone();
+					let score = Game::move_board(&mut after, movement, merge_mode);
+					(after != *grid).then(|| (movement, Chance::new(after, score)))
+				})
+				.collect()
+		});
+		if children.is_empty() {
+			// the game is lost
+			decision.visits += 1;
+			return 0.;
+		}
+
+		let parent_visits = decision.visits.max(1) as f64;
+		let best_mean = children
+			.iter()
+			.map(|(_, chance)| chance.mean())
+			.fold(0., f64::max);
+		let (_, chance) = children
+			.iter_mut()
+			.max_by(|(_, a), (_, b)| {
+				let a = self.uct(a, parent_visits, best_mean);
+				let b = self.uct(b, parent_visits, best_mean);
+				a.total_cmp(&b)
+			})
+			.expect("children is not empty");
+		let reward = chance.score as f64 + self.iterate_chance(chance, rng);
+		chance.visits += 1;
+		chance.total += reward;
+		decision.visits += 1;
+		decision.total += reward;
+		reward
+	}
+
+	/// sample a spawn, keep descending if its node exists or evaluate it with a rollout
+	fn iterate_chance(&self, chance: &mut Chance, rng: &mut impl Rng) -> f64 {
+		let mut spawned = chance.grid.clone();
+		self.spawn(&mut spawned, rng);
+		match chance
+			.children
+			.iter_mut()
+			.find(|decision| decision.grid == spawned)
+		{
+			Some(decision) => self.iterate(decision, rng),
+			None => {
+				let mut decision = Decision::new(spawned);
+				let reward = self.rollout(decision.grid.clone(), rng);
+				decision.visits += 1;
+				decision.total += reward;
+				chance.children.push(decision);
+				reward
+			}
+		}
+	}
+
+	/// score of random moves played from the grid
+	fn rollout(&self, mut grid: Grid, rng: &mut impl Rng) -> f64 {
+		let mut score = 0;
+		for _ in 0..self.rollout_length {
+			let mut moves = Move::all();
+			moves.shuffle(rng);
+			let moved = moves.into_iter().find_map(|movement| {
+				let mut after = grid.clone();
+				let move_score = Game::move_board(&mut after, movement, self.merge_mode);
+				(after != grid).then_some((after, move_score))
+			});
+			match moved {
+				Some((after, move_score)) => {
+					grid = after;
+					score += move_score;
+					self.spawn(&mut grid, rng);
+				}
+				None => break,
+			}
+		}
+		score as f64
+	}
+
+	/// place a tile from the spawn table on a random empty cell
+	fn spawn(&self, grid: &mut Grid, rng: &mut impl Rng) {
+		let empty_cells: Vec<_> = grid
+			.positions()
+			.filter(|&position| grid.get_val(position).is_none())
+			.collect();
+		if let Some(&position) = empty_cells.choose(rng) {
+			grid.set(position, Some(self.spawn_table.sample(rng)));
+		}
+	}
+
+	/// upper confidence bound of a child, its mean is scaled by the best one of its siblings
+	fn uct(&self, chance: &Chance, parent_visits: f64, best_mean: f64) -> f64 {
+		if chance.visits == 0 {
+			return f64::INFINITY;
+		}
+		let exploitation = if best_mean > 0. {
+			chance.mean() / best_mean
+		} else {
+			0.
+		};
+		let exploration = (parent_visits.ln() / chance.visits as f64).sqrt();
+		exploitation + self.exploration * exploration
+	}
+}
+
+impl Chance {
+	fn new(grid: Grid, score: usize) -> Self {
+		Self {
+			grid,
+			score,
+			visits: 0,
+			total: 0.,
+			children: vec![],
+		}
+	}
+
+	fn mean(&self) -> f64 {
+		if self.visits == 0 {
+			0.
+		} else {
+			self.total / self.visits as f64
+		}
+	}
+}
@@ -0,0 +1,284 @@
+use std::{
+	fs::File,
+	io::{self, BufReader, BufWriter, Read, Write},
+	path::Path,
+};
+
+use crate::{
+	game::{self, Game},
+	grid::Grid,
+};
+
+use super::{fallback_move, Controller, ControllerError, Move};
+
+/// number of different exponents a cell can hold in the network, bigger tiles share the last one
+const CELL_STATES: usize = 16;
+
+/// first bytes of weights files, with the version of the format
+const MAGIC: &[u8; 8] = b"rs48nt01";
+
+/// longest tuples read from weights files, each cell multiplies their weights by [`CELL_STATES`]
+const MAX_TUPLE_LENGTH: u32 = 6;
+
+///
+/// sum of weights looked up by the tiles of small groups of cells, see [`NTupleNetwork::train`]
+///
+#[derive(Debug, Clone)]
+pub struct NTupleNetwork {
+	width: usize,
+	height: usize,
+	tuples: Vec<Vec<(usize, usize)>>,
+	weights: Vec<Vec<f32>>,
+}
+
+impl NTupleNetwork {
+	///
+	/// constructor of a network with zero weights, its tuples are every 4 cells long segment
+	/// of the rows and columns and every 2×2 square of the grid
+	///
+	pub fn new(width: usize, height: usize) -> Self {
+		let segment = |length: usize| length.min(4);
+		let rows = (0..height).flat_map(|y| {
+			(0..=width - segment(width))
+				.map(move |x| (x..x + segment(width)).map(|x| (x, y)).collect())
+		});
+		let columns = (0..width).flat_map(|x| {
+			(0..=height - segment(height))
+				.map(move |y| (y..y + segment(height)).map(|y| (x, y)).collect())
+		});
+		let squares = (0..height.saturating_sub(1)).flat_map(|y| {
+			(0..width.saturating_sub(1))
+				.map(move |x| vec![(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)])
+		});
+		let tuples: Vec<Vec<_>> = rows.chain(columns).chain(squares).collect();
+		let weights = tuples
+			.iter()
+			.map(|tuple| vec![0.; CELL_STATES.pow(tuple.len() as u32)])
+			.collect();
+		Self {
+			width,
+			height,
+			tuples,
+			weights,
+		}
+	}
+
+	///
+	/// get the expected score that can still be made from a grid
+	///
+	pub fn evaluate(&self, grid: &Grid) -> f32 {
+		self.tuples
+			.iter()
+			.zip(&self.weights)
+			.map(|(tuple, weights)| weights[Self::index(tuple, grid)])
+			.sum()
+	}
+
+	///
+	/// get the move with the best sum of score and value of the grid after the move,
+	/// before any spawn, along with that sum and grid
+	///
+	pub fn best_move(&self, game: &Game) -> Option<(Move, f32, Grid)> {
+		game.legal_moves()
+			.into_iter()
+			.map(|movement| {
+				let mut after = game.get_board().clone();
+				let score = Game::move_board(&mut after, movement, game.get_merge_mode());
+				let value = score as f32 + self.evaluate(&after);
+				(movement, value, after)
+			})
+			.max_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+	}
+
+	///
+	/// play games against itself and learn from them with temporal difference learning,
+	/// the value of each grid after a move is moved towards the score of the next move
+	/// plus the value of the grid after it, `on_game` is called at the end of every game,
+	/// the games are seeded like a series, see [`Rules::nth_game`](game::Rules::nth_game)
+	///
+	pub fn train(
+		&mut self,
+		rules: &game::Rules,
+		games: usize,
+		learning_rate: f32,
+		mut on_game: impl FnMut(usize, &Game),
+	) -> io::Result<()> {
+		self.check_size(rules.get_width(), rules.get_height())?;
+		let learning_rate = learning_rate / self.tuples.len() as f32;
+		for index in 0..games {
+			let mut game = Game::new(rules.nth_game(index).undo_limit(Some(0)));
+			let mut previous: Option<Grid> = None;
+			loop {
+				let best = self.best_move(&game);
+				let target = best.as_ref().map(|(_, value, _)| *value).unwrap_or(0.);
+				if let Some(previous) = &previous {
+					let error = target - self.evaluate(previous);
+					self.update(previous, learning_rate * error);
+				}
+				let Some((movement, _, after)) = best else {
+					break;
+				};
+				previous = Some(after);
+				if game.quick_turn(movement).is_err() {
+					break;
+				}
+			}
+			on_game(index, &game);
+		}
+		Ok(())
+	}
+
+	///
+	/// write the network to a file
+	///
+	pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+		let mut file = BufWriter::new(File::create(path)?);
+		file.write_all(MAGIC)?;
+		for number in [self.width, self.height, self.tuples.len()] {
+			file.write_all(&(number as u32).to_le_bytes())?;
+		}
+		for (tuple, weights) in self.tuples.iter().zip(&self.weights) {
+			file.write_all(&(tuple.len() as u32).to_le_bytes())?;
+			for &(x, y) in tuple {
+				file.write_all(&(x as u32).to_le_bytes())?;
+				file.write_all(&(y as u32).to_le_bytes())?;
+			}
+			for weight in weights {
+				file.write_all(&weight.to_le_bytes())?;
+			}
+		}
+		file.flush()
+	}
+
+	///
+	/// read a network written by [`NTupleNetwork::save`]
+	///
+	pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+		let mut file = BufReader::new(File::open(path)?);
+		let mut magic = [0; MAGIC.len()];
+		file.read_exact(&mut magic)?;
+		if &magic != MAGIC {
+			return Err(invalid_data("not a weights file of a supported version"));
+		}
+		let width = read_u32(&mut file)? as usize;
+		let height = read_u32(&mut file)? as usize;
+		let tuple_count = read_u32(&mut file)?;
+		let mut tuples = vec![];
+		let mut weights = vec![];
+		for _ in 0..tuple_count {
+			let length = read_u32(&mut file)?;
+			if length > MAX_TUPLE_LENGTH {
+				return Err(invalid_data("tuple is too long"));
+			}
+			let tuple = (0..length)
+				.map(|_| Ok((read_u32(&mut file)? as usize, read_u32(&mut file)? as usize)))
+				.collect::<io::Result<Vec<_>>>()?;
+			if tuple.iter().any(|&(x, y)| x >= width || y >= height) {
+				return Err(invalid_data("tuple is out of the grid"));
+			}
+			// pushed one by one so that a truncated file fails before allocating every weight
+			let mut tuple_weights = vec![];
+			for _ in 0..CELL_STATES.pow(length) {
+				tuple_weights.push(read_f32(&mut file)?);
+			}
+			tuples.push(tuple);
+			weights.push(tuple_weights);
+		}
+		Ok(Self {
+			width,
+			height,
+			tuples,
+			weights,
+		})
+	}
+
+	/// get the number of columns of the grids the network evaluates
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	/// get the number of rows of the grids the network evaluates
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	///
+	/// fail unless the network evaluates grids of that size
+	///
+	pub fn check_size(&self, width: usize, height: usize) -> io::Result<()> {
+		if (width, height) == (self.width, self.height) {
+			return Ok(());
+		}
+		let message = format!(
+			"the network evaluates {}x{} grids, not {width}x{height} ones",
+			self.width, self.height
+		);
+		Err(io::Error::new(io::ErrorKind::InvalidInput, message))
+	}
+
+	fn update(&mut self, grid: &Grid, delta: f32) {
+		for (tuple, weights) in self.tuples.iter().zip(&mut self.weights) {
+			weights[Self::index(tuple, grid)] += delta;
+		}
+	}
+
+	/// index in the weights of a tuple of the exponents of its cells
+	fn index(tuple: &[(usize, usize)], grid: &Grid) -> usize {
+		tuple.iter().fold(0, |index, &position| {
+			let exponent = match grid.get_val(position) {
+				Some(value) => (usize::BITS - value.leading_zeros()) as usize,
+				None => 0,
+			};
+			index * CELL_STATES + exponent.min(CELL_STATES - 1)
+		})
+	}
+}
+
+///
+/// plays the move a trained [`NTupleNetwork`] values the most
+///
+pub struct NTupleController {
+	network: NTupleNetwork,
+}
+
+impl NTupleController {
+	pub fn new(network: NTupleNetwork) -> Self {
+		Self { network }
+	}
+
+	///
+	/// constructor from a file written by [`NTupleNetwork::save`], for grids of that size
+	///
+	pub fn load(path: impl AsRef<Path>, width: usize, height: usize) -> io::Result<Self> {
+		let network = NTupleNetwork::load(path)?;
+		network.check_size(width, height)?;
+		Ok(Self::new(network))
+	}
+}
+
+impl Controller for NTupleController {
+	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
+		let movement = self
+			.network
+			.best_move(game)
+			.map(|(movement, _, _)| movement)
+			.unwrap_or_else(|| fallback_move(game));
+		Ok(movement)
+	}
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+	let mut bytes = [0; 4];
+	reader.read_exact(&mut bytes)?;
+	Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+	let mut bytes = [0; 4];
+	reader.read_exact(&mut bytes)?;
+	Ok(f32::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
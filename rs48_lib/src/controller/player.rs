@@ -1,29 +1,62 @@
 use std::io::{stdin, stdout};
 use termion::{event::Key, input::TermRead, raw::IntoRawMode};
 
-use super::{Controller, ControllerError, Move};
+use super::{Action, Controller, ControllerError, Move};
 use crate::game::Game;
 
+///
+/// plays with the arrow keys, 'u' undoes a turn, 'r' redoes it, 's' saves the game and 'q' quits
+///
 #[derive(Debug, Default)]
 pub struct PlayerController;
 
 impl Controller for PlayerController {
-	fn next_move(&mut self, _game: &Game) -> Result<Move, ControllerError> {
+	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
+		loop {
+			if let Action::Move(movement) = self.next_action(game)? {
+				return Ok(movement);
+			}
+		}
+	}
+
+	fn next_action(&mut self, _game: &Game) -> Result<Action, ControllerError> {
 		let stdin = stdin();
 		let mut _stdout = stdout()
 			.into_raw_mode()
 			.expect("terminal needs to be set into raw mode");
 		for c in stdin.keys() {
-			let movement = match c.expect("key should be readable") {
+			let action = match c.expect("key should be readable") {
 				Key::Char('q') => return Err(ControllerError::ExitSignal),
-				Key::Left => Move::LEFT,
-				Key::Right => Move::RIGHT,
-				Key::Up => Move::UP,
-				Key::Down => Move::DOWN,
+				Key::Char('u') => Action::Undo,
+				Key::Char('r') => Action::Redo,
+				Key::Char('s') => Action::Save,
+				Key::Left => Action::Move(Move::LEFT),
+				Key::Right => Action::Move(Move::RIGHT),
+				Key::Up => Action::Move(Move::UP),
+				Key::Down => Action::Move(Move::DOWN),
 				_ => continue,
 			};
-			return Ok(movement);
+			return Ok(action);
 		}
 		unreachable!()
 	}
+
+	fn keep_going(&mut self, _game: &Game) -> Result<bool, ControllerError> {
+		let stdin = stdin();
+		let mut _stdout = stdout()
+			.into_raw_mode()
+			.expect("terminal needs to be set into raw mode");
+		for c in stdin.keys() {
+			match c.expect("key should be readable") {
+				Key::Char('c') => return Ok(true),
+				Key::Char('q') => return Ok(false),
+				_ => continue,
+			}
+		}
+		unreachable!()
+	}
+
+	fn interactive(&self) -> bool {
+		true
+	}
 }
@@ -1,14 +1,18 @@
-use rand::random;
+use rand::{seq::SliceRandom, thread_rng};
 
-use super::{Controller, ControllerError, Move};
+use super::{fallback_move, Controller, ControllerError, Move};
 use crate::game::Game;
 
 #[derive(Debug, Default)]
 pub struct RandomController;
 
 impl Controller for RandomController {
-	fn next_move(&mut self, _game: &Game) -> Result<Move, ControllerError> {
-		let movement = random();
+	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
+		let movement = game
+			.legal_moves()
+			.choose(&mut thread_rng())
+			.copied()
+			.unwrap_or_else(|| fallback_move(game));
 		Ok(movement)
 	}
 }
@@ -1,14 +1,63 @@
-use std::ops::Add;
+use std::{fmt::Display, str::FromStr};
 
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
 use crate::{game::Game, prelude::RandomController};
 
-use super::{Controller, ControllerError, Move};
+use super::{fallback_move, Controller, ControllerError, Move};
 
+/// what the simulations of a move are rated on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+	/// points scored during the simulation
+	ScoreDelta,
+	/// turns played before the simulation ends or the game is lost
+	Survival,
+	/// biggest tile at the end of the simulation
+	MaxTile,
+}
+
+impl Objective {
+	fn rate(&self, initial: &Game, simulated: &Game) -> f64 {
+		match self {
+			Self::ScoreDelta => simulated.get_score().saturating_sub(initial.get_score()) as f64,
+			Self::Survival => (simulated.get_turn_index() - initial.get_turn_index()) as f64,
+			Self::MaxTile => simulated.get_board().biggest_value() as f64,
+		}
+	}
+}
+
+impl Display for Objective {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::ScoreDelta => "score",
+			Self::Survival => "survival",
+			Self::MaxTile => "max-tile",
+		})
+	}
+}
+
+impl FromStr for Objective {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"score" => Ok(Self::ScoreDelta),
+			"survival" => Ok(Self::Survival),
+			"max-tile" => Ok(Self::MaxTile),
+			_ => Err(format!("failed to parse '{s}' into an 'Objective'")),
+		}
+	}
+}
+
+///
+/// plays every legal move followed by simulated games and picks the move whose simulations rate best
+///
 pub struct SimulatedController {
 	simulations_per_move: usize,
 	length_of_simulation: usize,
+	rollout_policy: Box<dyn Fn() -> Box<dyn Controller> + Send + Sync>,
+	objective: Objective,
 }
 
 impl SimulatedController {
@@ -16,41 +65,59 @@ impl SimulatedController {
 		Self {
 			simulations_per_move,
 			length_of_simulation,
+			rollout_policy: Box::new(|| RandomController.into_box()),
+			objective: Objective::ScoreDelta,
 		}
 	}
+
+	/// creates the controller playing each simulation
+	pub fn rollout_policy(
+		mut self,
+		rollout_policy: impl Fn() -> Box<dyn Controller> + Send + Sync + 'static,
+	) -> Self {
+		self.rollout_policy = Box::new(rollout_policy);
+		self
+	}
+
+	/// what the simulations are rated on
+	pub fn objective(mut self, objective: Objective) -> Self {
+		self.objective = objective;
+		self
+	}
 }
 
 impl Controller for SimulatedController {
 	fn next_move(&mut self, game: &Game) -> Result<Move, ControllerError> {
-		let initial_score = game.get_score();
-
-		let mut scores: Vec<_> = Move::all()
+		let mut scores: Vec<_> = game
+			.legal_moves()
 			.into_par_iter()
 			.map(|initial_move| {
 				let sim_scores = (0..self.simulations_per_move).into_par_iter().map(|_| {
-					let mut game = game.clone();
+					let mut simulated = game.fork();
 
-					game.turn(initial_move.clone()).ok();
-					let mut controller = RandomController::default();
+					simulated.quick_turn(initial_move).ok();
+					let mut controller = (self.rollout_policy)();
 					for _ in 1..self.length_of_simulation {
-						let movement = controller.next_move(&game).ok();
-						let result = movement.and_then(|movement| game.turn(movement).ok());
+						let movement = controller.next_move(&simulated).ok();
+						let result =
+							movement.and_then(|movement| simulated.quick_turn(movement).ok());
 						if result.is_none() {
 							break;
 						}
 					}
-					game.get_score() - initial_score
+					self.objective.rate(game, &simulated)
 				});
 
 				let sim_scores: Vec<_> = sim_scores.collect();
-				let avg =
-					sim_scores.iter().cloned().reduce(Add::add).unwrap_or(0) / sim_scores.len();
+				let avg = sim_scores.iter().sum::<f64>() / sim_scores.len().max(1) as f64;
 				(initial_move, avg)
 			})
 			.collect();
-		scores.sort_by(|(_, a), (_, b)| b.cmp(a));
+		scores.sort_by(|(_, a), (_, b)| b.total_cmp(a));
 
-		let (m, _) = scores.first().unwrap();
-		Ok(m.clone())
+		let movement = scores
+			.first()
+			.map_or_else(|| fallback_move(game), |&(m, _)| m);
+		Ok(movement)
 	}
 }
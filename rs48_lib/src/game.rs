@@ -1,19 +1,234 @@
-use std::{error::Error, fmt::Display};
+use std::{
+	error::Error,
+	fmt::Display,
+	fs::File,
+	io::{self, BufReader, BufWriter, Read, Write},
+	path::Path,
+	str::FromStr,
+	sync::Arc,
+};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 use super::{
+	bitboard::BitBoard,
 	controller::{ControllerError, Move},
 	grid::Grid,
+	history::{History, Snapshot},
+	turn_outcome::{self, Spawn, TurnOutcome},
 };
 
-#[derive(Debug, Clone)]
+/// how tiles that already merged during a move are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMode {
+	/// a tile can only merge once per move, `[1, 1, 2]` moved left gives `[2, 2]`
+	Standard,
+	/// a merged tile can merge again with the next one, `[1, 1, 2]` moved left gives `[4]`
+	Cascading,
+}
+
+/// values that can spawn on the grid, each with a relative weight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct SpawnTable {
+	entries: Arc<[(usize, u32)]>,
+}
+
+impl SpawnTable {
+	///
+	/// constructor, entries without weight are left out, fails when none is left,
+	/// when a value is 0 or too big to be doubled up to its classic target,
+	/// or when the weights add up to more than a `u32` holds
+	///
+	pub fn new(entries: Vec<(usize, u32)>) -> Result<Self, String> {
+		let entries: Arc<[_]> = entries
+			.into_iter()
+			.filter(|&(_, weight)| weight > 0)
+			.collect();
+		if entries.is_empty() {
+			return Err("the spawn table needs an entry with a positive weight".to_string());
+		}
+		for &(value, _) in entries.iter() {
+			if value == 0 {
+				return Err("spawned values cannot be 0, they would merge forever".to_string());
+			}
+			if value.checked_mul(1024).is_none() {
+				return Err(format!(
+					"the spawned value {value} is too big, doubling it up to the target would overflow"
+				));
+			}
+		}
+		entries
+			.iter()
+			.try_fold(0u32, |total, &(_, weight)| total.checked_add(weight))
+			.ok_or("the weights of the spawn table add up to more than 4294967295")?;
+		Ok(Self { entries })
+	}
+
+	///
+	/// the spawns of the original 2048, 2 at 90% and 4 at 10%
+	///
+	pub fn classic() -> Self {
+		Self {
+			entries: Arc::new([(2, 9), (4, 1)]),
+		}
+	}
+
+	///
+	/// only spawns 1
+	///
+	pub fn ones() -> Self {
+		Self {
+			entries: Arc::new([(1, 1)]),
+		}
+	}
+
+	///
+	/// get the (value, weight) pairs of the table
+	///
+	pub fn entries(&self) -> &[(usize, u32)] {
+		&self.entries
+	}
+
+	///
+	/// the tile worth the 2048 of the original game, 1024 times the smallest value
+	///
+	pub fn classic_target(&self) -> usize {
+		let unit = self.entries.iter().map(|&(value, _)| value).min();
+		1024 * unit.expect("tables have entries")
+	}
+
+	///
+	/// get the smallest value if every other one is that value times a power of two
+	///
+	pub fn unit(&self) -> Option<usize> {
+		let unit = self.entries.iter().map(|&(value, _)| value).min()?;
+		let is_multiple =
+			|value: usize| value.is_multiple_of(unit) && (value / unit).is_power_of_two();
+		if unit > 0 && self.entries.iter().all(|&(value, _)| is_multiple(value)) {
+			Some(unit)
+		} else {
+			None
+		}
+	}
+
+	///
+	/// get the sum of the weights, it fits in a `u32` as the constructor checks it
+	///
+	pub fn total_weight(&self) -> u32 {
+		self.entries.iter().map(|&(_, weight)| weight).sum()
+	}
+
+	///
+	/// pick a value according to the weights
+	///
+	pub fn sample(&self, rng: &mut impl Rng) -> usize {
+		let mut picked = rng.gen_range(0..self.total_weight());
+		for &(value, weight) in self.entries.iter() {
+			if picked < weight {
+				return value;
+			}
+			picked -= weight;
+		}
+		unreachable!("picked weight is below the total")
+	}
+}
+
+impl Default for SpawnTable {
+	fn default() -> Self {
+		Self::ones()
+	}
+}
+
+impl Display for SpawnTable {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if *self == Self::classic() {
+			f.write_str("classic")
+		} else if *self == Self::ones() {
+			f.write_str("ones")
+		} else {
+			let entries: Vec<_> = self
+				.entries
+				.iter()
+				.map(|(value, weight)| format!("{value}:{weight}"))
+				.collect();
+			f.write_str(&entries.join(","))
+		}
+	}
+}
+
+impl FromStr for SpawnTable {
+	type Err = String;
+
+	/// either `classic`, `ones` or a list of `value:weight` pairs like `2:9,4:1`
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"classic" => Ok(Self::classic()),
+			"ones" => Ok(Self::ones()),
+			_ => {
+				let entries = s
+					.split(',')
+					.map(|entry| {
+						let (value, weight) = entry.split_once(':')?;
+						Some((value.trim().parse().ok()?, weight.trim().parse().ok()?))
+					})
+					.collect::<Option<Vec<_>>>()
+					.ok_or_else(|| format!("failed to parse '{s}' into a 'SpawnTable'"))?;
+				Self::new(entries)
+			}
+		}
+	}
+}
+
+impl From<SpawnTable> for String {
+	fn from(spawn_table: SpawnTable) -> Self {
+		spawn_table.to_string()
+	}
+}
+
+impl TryFrom<String> for SpawnTable {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rules {
-	size: usize,
+	width: usize,
+	height: usize,
 	spawn_per_turn: usize,
+	seed: Option<u64>,
+	merge_mode: MergeMode,
+	target_tile: Option<usize>,
+	/// wether the target is still the classic one of the spawn table, until one is set
+	#[serde(skip)]
+	classic_target: bool,
+	keep_going: bool,
+	spawn_table: SpawnTable,
+	undo_limit: Option<usize>,
+	undo_penalty: usize,
 }
 
 impl Rules {
-	pub fn size(mut self, size: usize) -> Self {
-		self.size = size;
+	/// makes the grid a square of that size
+	pub fn size(self, size: usize) -> Self {
+		self.width(size).height(size)
+	}
+
+	/// number of columns of the grid
+	pub fn width(mut self, width: usize) -> Self {
+		self.width = width;
+		self
+	}
+
+	/// number of rows of the grid
+	pub fn height(mut self, height: usize) -> Self {
+		self.height = height;
 		self
 	}
 
@@ -21,13 +236,104 @@ impl Rules {
 		self.spawn_per_turn = spawn_per_turn;
 		self
 	}
+
+	/// seed of the rng deciding spawns, a random one is picked when none is given
+	pub fn seed(mut self, seed: Option<u64>) -> Self {
+		self.seed = seed;
+		self
+	}
+
+	/// wether merged tiles can merge again during the same move
+	pub fn merge_mode(mut self, merge_mode: MergeMode) -> Self {
+		self.merge_mode = merge_mode;
+		self
+	}
+
+	/// tile value that wins the game once reached, no victory is possible without one,
+	/// by default the [classic target](SpawnTable::classic_target) of the spawn table
+	pub fn target_tile(mut self, target_tile: Option<usize>) -> Self {
+		self.target_tile = target_tile;
+		self.classic_target = false;
+		self
+	}
+
+	/// wether to keep playing silently after reaching the target tile
+	pub fn keep_going(mut self, keep_going: bool) -> Self {
+		self.keep_going = keep_going;
+		self
+	}
+
+	/// values of the spawned tiles and their odds
+	pub fn spawn_table(mut self, spawn_table: SpawnTable) -> Self {
+		self.spawn_table = spawn_table;
+		self
+	}
+
+	/// number of turns that can be undone in a row, without limit if there is none
+	pub fn undo_limit(mut self, undo_limit: Option<usize>) -> Self {
+		self.undo_limit = undo_limit;
+		self
+	}
+
+	/// points removed from the score for every undo
+	pub fn undo_penalty(mut self, undo_penalty: usize) -> Self {
+		self.undo_penalty = undo_penalty;
+		self
+	}
+
+	pub fn get_width(&self) -> usize {
+		self.width
+	}
+
+	pub fn get_height(&self) -> usize {
+		self.height
+	}
+
+	pub fn get_seed(&self) -> Option<u64> {
+		self.seed
+	}
+
+	pub fn get_merge_mode(&self) -> MergeMode {
+		self.merge_mode
+	}
+
+	pub fn get_spawn_table(&self) -> &SpawnTable {
+		&self.spawn_table
+	}
+
+	///
+	/// get the rules of the game at `index` in a series of games, when the rules have a seed
+	/// it is the seed of the first game and the next games use the following seeds
+	///
+	pub fn nth_game(&self, index: usize) -> Self {
+		let seed = self.seed.map(|seed| seed.wrapping_add(index as u64));
+		self.clone().seed(seed)
+	}
+
+	/// replace the classic target by its value for the spawn table
+	fn resolve_target(mut self) -> Self {
+		if self.classic_target {
+			self.target_tile = Some(self.spawn_table.classic_target());
+			self.classic_target = false;
+		}
+		self
+	}
 }
 
 impl Default for Rules {
 	fn default() -> Self {
 		Self {
-			size: 4,
+			width: 4,
+			height: 4,
 			spawn_per_turn: 1,
+			seed: None,
+			merge_mode: MergeMode::Standard,
+			target_tile: None,
+			classic_target: true,
+			keep_going: false,
+			spawn_table: SpawnTable::default(),
+			undo_limit: Some(100),
+			undo_penalty: 0,
 		}
 	}
 }
@@ -35,7 +341,9 @@ impl Default for Rules {
 #[derive(Debug)]
 pub enum GameError {
 	GridIsFull,
+	InvalidMove,
 	ControllerError(ControllerError),
+	Io(io::Error),
 }
 
 impl From<ControllerError> for GameError {
@@ -44,48 +352,149 @@ impl From<ControllerError> for GameError {
 	}
 }
 
+impl From<io::Error> for GameError {
+	fn from(error: io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
 impl Display for GameError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Self::GridIsFull => f.write_str("grid is full"),
+			Self::InvalidMove => f.write_str("move does not change the grid"),
 			GameError::ControllerError(err) => err.fmt(f),
+			GameError::Io(err) => err.fmt(f),
 		}
 	}
 }
 
 impl Error for GameError {}
 
+/// state of the game regarding its end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+	/// moves can still be played
+	Ongoing,
+	/// the target tile is on the grid and the player did not choose to keep going yet
+	Won,
+	/// no move can change the grid anymore
+	Lost,
+}
+
+/// version of the save files written by this version of the game
+const SAVE_VERSION: u32 = 1;
+
+/// only the version of a save or replay file, read first as other versions may have other fields
+#[derive(Deserialize)]
+struct FileVersion {
+	version: u32,
+}
+
+/// fail unless the JSON text is a file of that version, `kind` names the file in the error
+pub(crate) fn check_version(text: &str, version: u32, kind: &str) -> io::Result<()> {
+	let FileVersion { version: found } = serde_json::from_str(text)?;
+	if found != version {
+		let message = format!("unsupported {kind} version {found}");
+		return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+	}
+	Ok(())
+}
+
+/// contents of a save file, see [`Game::save`]
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+	version: u32,
+	rules: Rules,
+	/// rows of tile values
+	board: Vec<Vec<Option<usize>>>,
+	score: usize,
+	penalties: usize,
+	turn_index: usize,
+	keep_going: bool,
+	seed: u64,
+	/// position of the rng in its stream of numbers
+	rng_word_pos: u128,
+}
+
 #[derive(Clone)]
 pub struct Game {
 	board: Grid,
 	score: usize,
 	turn_index: usize,
 	spawn_per_turn: usize,
+	seed: u64,
+	rng: ChaCha8Rng,
+	merge_mode: MergeMode,
+	target_tile: Option<usize>,
+	keep_going: bool,
+	spawn_table: SpawnTable,
+	history: History,
+	undo_penalty: usize,
+	penalties: usize,
 	rules: Rules,
 }
 
 impl Game {
 	pub fn new(rules: Rules) -> Self {
+		let rules = rules.resolve_target();
 		let Rules {
-			size,
+			width,
+			height,
 			spawn_per_turn,
-		} = rules;
+			seed,
+			merge_mode,
+			target_tile,
+			classic_target: _,
+			keep_going,
+			spawn_table,
+			undo_limit,
+			undo_penalty,
+		} = rules.clone();
+		let seed = seed.unwrap_or_else(rand::random);
 
-		Self {
-			board: Grid::new(size),
+		let board = match spawn_table.unit() {
+			// 4×4 games with tiles that fit in a bitboard use it as a faster backend
+			Some(unit)
+				if width == BitBoard::SIZE
+					&& height == BitBoard::SIZE
+					&& merge_mode == MergeMode::Standard =>
+			{
+				Grid::new_packed(unit)
+			}
+			_ => Grid::new(width, height),
+		};
+
+		let mut game = Self {
+			board,
 			score: 0,
 			turn_index: 0,
 			spawn_per_turn,
+			seed,
+			rng: ChaCha8Rng::seed_from_u64(seed),
+			merge_mode,
+			target_tile,
+			keep_going,
+			spawn_table,
+			history: History::new(undo_limit),
+			undo_penalty,
+			penalties: 0,
 			rules,
-		}
+		};
+		// an empty grid has no legal move, the game starts with a first spawn
+		game.spawn_tiles();
+		game
 	}
 
 	pub fn get_board(&self) -> &Grid {
 		&self.board
 	}
 
+	///
+	/// get the points earned by merges minus the penalties of undos
+	///
 	pub fn get_score(&self) -> usize {
-		self.score
+		self.score.saturating_sub(self.penalties)
 	}
 
 	pub fn get_rules(&self) -> &Rules {
@@ -96,19 +505,277 @@ impl Game {
 		self.turn_index
 	}
 
-	pub fn turn(&mut self, movement: Move) -> Result<(), GameError> {
-		self.perform_move(movement);
-		for _ in 0..self.spawn_per_turn {
-			self.spawn_random()?;
+	/// seed the rng of the game was created with
+	pub fn get_seed(&self) -> u64 {
+		self.seed
+	}
+
+	pub fn get_spawn_table(&self) -> &SpawnTable {
+		&self.spawn_table
+	}
+
+	pub fn get_merge_mode(&self) -> MergeMode {
+		self.merge_mode
+	}
+
+	///
+	/// copy the game for a simulation, with its own spawns and without recording history
+	///
+	pub fn fork(&self) -> Self {
+		let seed = rand::random();
+		// listed field by field, cloning the game would copy the whole history only to drop it
+		Self {
+			board: self.board.clone(),
+			score: self.score,
+			turn_index: self.turn_index,
+			spawn_per_turn: self.spawn_per_turn,
+			seed,
+			rng: ChaCha8Rng::seed_from_u64(seed),
+			merge_mode: self.merge_mode,
+			target_tile: self.target_tile,
+			keep_going: self.keep_going,
+			spawn_table: self.spawn_table.clone(),
+			history: History::new(Some(0)),
+			undo_penalty: self.undo_penalty,
+			penalties: self.penalties,
+			rules: self.rules.clone(),
+		}
+	}
+
+	///
+	/// play a move and spawn new tiles, return what happened with the slides and merges of the move,
+	/// a move that leaves the grid unchanged is refused and does not count as a turn
+	///
+	pub fn turn(&mut self, movement: Move) -> Result<TurnOutcome, GameError> {
+		let (slides, merges) = turn_outcome::describe_move(&self.board, movement, self.merge_mode);
+		let outcome = self.quick_turn(movement)?;
+		Ok(TurnOutcome {
+			slides,
+			merges,
+			..outcome
+		})
+	}
+
+	///
+	/// play a turn like [`Game::turn`] without describing the move, the outcome has no slides nor merges,
+	/// for simulations and games nobody watches
+	///
+	pub fn quick_turn(&mut self, movement: Move) -> Result<TurnOutcome, GameError> {
+		let snapshot = self.history.records().then(|| self.snapshot());
+		// without history only the grid is kept, to see if the move changes it
+		let board = snapshot.is_none().then(|| self.board.clone());
+		let score = self.perform_move(movement);
+		let previous = snapshot.as_ref().map(|snapshot| &snapshot.board);
+		if Some(&self.board) == previous.or(board.as_ref()) {
+			return Err(if self.legal_moves().is_empty() {
+				GameError::GridIsFull
+			} else {
+				GameError::InvalidMove
+			});
 		}
+		let spawns = self.spawn_tiles();
 		self.turn_index += 1;
-		Ok(())
+		if let Some(snapshot) = snapshot {
+			self.history.push(snapshot);
+		}
+		Ok(TurnOutcome {
+			movement,
+			score,
+			slides: vec![],
+			merges: vec![],
+			spawns,
+		})
+	}
+
+	///
+	/// go back to the state before the last turn, return wether there was one to go back to
+	///
+	pub fn undo(&mut self) -> bool {
+		match self.history.undo(self.snapshot()) {
+			Some(previous) => {
+				self.restore(previous);
+				self.penalties += self.undo_penalty;
+				true
+			}
+			None => false,
+		}
+	}
+
+	///
+	/// replay the last undone turn, return wether there was one to replay
+	///
+	pub fn redo(&mut self) -> bool {
+		match self.history.redo(self.snapshot()) {
+			Some(next) => {
+				self.restore(next);
+				true
+			}
+			None => false,
+		}
+	}
+
+	///
+	/// get the past and undone states of the game
+	///
+	pub fn get_history(&self) -> &History {
+		&self.history
+	}
+
+	///
+	/// write the game to a file as JSON, everything but its history is kept,
+	/// so that the loaded game continues with the same spawns
+	///
+	pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+		let board = (0..self.board.height())
+			.map(|y| {
+				(0..self.board.width())
+					.map(|x| self.board.get_val((x, y)))
+					.collect()
+			})
+			.collect();
+		let saved = SavedGame {
+			version: SAVE_VERSION,
+			rules: self.rules.clone(),
+			board,
+			score: self.score,
+			penalties: self.penalties,
+			turn_index: self.turn_index,
+			keep_going: self.keep_going,
+			seed: self.seed,
+			rng_word_pos: self.rng.get_word_pos(),
+		};
+		let mut file = BufWriter::new(File::create(path)?);
+		serde_json::to_writer(&mut file, &saved)?;
+		file.flush()
+	}
+
+	///
+	/// read a game written by [`Game::save`]
+	///
+	pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+		let mut text = String::new();
+		BufReader::new(File::open(path)?).read_to_string(&mut text)?;
+		check_version(&text, SAVE_VERSION, "save")?;
+		let saved: SavedGame = serde_json::from_str(&text)?;
+		let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+		let SavedGame {
+			rules,
+			board,
+			score,
+			penalties,
+			turn_index,
+			keep_going,
+			seed,
+			rng_word_pos,
+			..
+		} = saved;
+		let (width, height) = (rules.get_width(), rules.get_height());
+		if board.len() != height || board.iter().any(|row| row.len() != width) {
+			return Err(invalid_data("board does not match the rules".to_string()));
+		}
+
+		let mut game = Self::new(rules.seed(Some(seed)));
+		for position in game.board.positions().collect::<Vec<_>>() {
+			game.board.set(position, None);
+		}
+		for (y, row) in board.into_iter().enumerate() {
+			for (x, value) in row.into_iter().enumerate() {
+				game.board.set((x, y), value);
+			}
+		}
+		game.score = score;
+		game.penalties = penalties;
+		game.turn_index = turn_index;
+		game.keep_going = keep_going;
+		game.rng.set_word_pos(rng_word_pos);
+		Ok(game)
+	}
+
+	fn snapshot(&self) -> Snapshot {
+		Snapshot {
+			board: self.board.clone(),
+			score: self.score,
+			turn_index: self.turn_index,
+			keep_going: self.keep_going,
+			rng: self.rng.clone(),
+		}
+	}
+
+	fn restore(&mut self, snapshot: Snapshot) {
+		let Snapshot {
+			board,
+			score,
+			turn_index,
+			keep_going,
+			rng,
+		} = snapshot;
+		self.board = board;
+		self.score = score;
+		self.turn_index = turn_index;
+		self.keep_going = keep_going;
+		self.rng = rng;
 	}
 
-	fn spawn_random(&mut self) -> Result<(), GameError> {
+	/// spawn the tiles of a turn, as many as the grid can hold
+	fn spawn_tiles(&mut self) -> Vec<Spawn> {
+		(0..self.spawn_per_turn)
+			.map_while(|_| self.spawn_random())
+			.collect()
+	}
+
+	///
+	/// get wether the game can go on, a game with no legal move is lost
+	///
+	pub fn status(&self) -> GameStatus {
+		if self.reached_target() && !self.keep_going {
+			GameStatus::Won
+		} else if self.legal_moves().is_empty() {
+			GameStatus::Lost
+		} else {
+			GameStatus::Ongoing
+		}
+	}
+
+	///
+	/// wether a tile of the target value or above is on the grid
+	///
+	pub fn reached_target(&self) -> bool {
+		match self.target_tile {
+			Some(target_tile) => self.board.biggest_value() >= target_tile,
+			None => false,
+		}
+	}
+
+	///
+	/// keep playing after a victory, the game is then only ongoing until lost
+	///
+	pub fn keep_going(&mut self) {
+		self.keep_going = true;
+	}
+
+	///
+	/// get the moves that would change the grid
+	///
+	pub fn legal_moves(&self) -> Vec<Move> {
+		Move::all()
+			.into_iter()
+			.filter(|&movement| self.is_legal(movement))
+			.collect()
+	}
+
+	///
+	/// wether playing that move would change the grid
+	///
+	pub fn is_legal(&self, movement: Move) -> bool {
+		let mut board = self.board.clone();
+		Self::move_board(&mut board, movement, self.merge_mode);
+		board != self.board
+	}
+
+	fn spawn_random(&mut self) -> Option<Spawn> {
 		let mut potentials = vec![];
-		for x in 0..self.board.size() {
-			for y in 0..self.board.size() {
+		for x in 0..self.board.width() {
+			for y in 0..self.board.height() {
 				if self
 					.board
 					.get((x, y))
@@ -121,65 +788,92 @@ impl Game {
 		}
 		let potential_count = potentials.len() as f32;
 		if potential_count == 0. {
-			return Err(GameError::GridIsFull);
+			return None;
 		}
-		let random = rand::random::<f32>() * potential_count;
+		let random = self.rng.gen::<f32>() * potential_count;
 		let index = random.floor() as usize;
 		let (x, y) = potentials[index];
-		self.board.set((x, y), Some(1));
-		Ok(())
+		let value = self.spawn_table.sample(&mut self.rng);
+		self.board.set((x, y), Some(value));
+		Some(Spawn {
+			position: (x, y),
+			value,
+		})
 	}
 
 	pub fn perform_move(&mut self, movement: Move) -> usize {
+		let move_score = Self::move_board(&mut self.board, movement, self.merge_mode);
+		self.score += move_score;
+		move_score
+	}
+
+	///
+	/// slide and merge the tiles of any grid, return the score of the move
+	///
+	pub fn move_board(board: &mut Grid, movement: Move, merge_mode: MergeMode) -> usize {
+		if let Some(packed) = board.packed_mut() {
+			if !packed.is_saturated() && merge_mode == MergeMode::Standard {
+				return packed.perform_move(movement);
+			}
+			// the packed grid can not represent the result of every merge anymore
+			board.unpack();
+		}
 		let mut move_score = 0;
+		// positions of the tiles resulting from a merge during this move
+		let mut merged = vec![];
+		let (width, height) = (board.width(), board.height());
+		let mut linear_move = |direction, tile_pos| {
+			Self::perform_linear_move(board, direction, tile_pos, merge_mode, &mut merged)
+		};
 		match movement {
 			Move::LEFT => {
-				for y in 0..self.board.size() {
-					for x in 0..self.board.size() {
-						move_score += self.perform_linear_move((-1, 0), (x, y));
+				for y in 0..height {
+					for x in 0..width {
+						move_score += linear_move((-1, 0), (x, y));
 					}
 				}
 			}
 			Move::RIGHT => {
-				for y in 0..self.board.size() {
-					for x in (0..self.board.size()).rev() {
-						move_score += self.perform_linear_move((1, 0), (x, y));
+				for y in 0..height {
+					for x in (0..width).rev() {
+						move_score += linear_move((1, 0), (x, y));
 					}
 				}
 			}
 			Move::UP => {
-				for x in 0..self.board.size() {
-					for y in 0..self.board.size() {
-						move_score += self.perform_linear_move((0, -1), (x, y));
+				for x in 0..width {
+					for y in 0..height {
+						move_score += linear_move((0, -1), (x, y));
 					}
 				}
 			}
 			Move::DOWN => {
-				for x in 0..self.board.size() {
-					for y in (0..self.board.size()).rev() {
-						move_score += self.perform_linear_move((0, 1), (x, y));
+				for x in 0..width {
+					for y in (0..height).rev() {
+						move_score += linear_move((0, 1), (x, y));
 					}
 				}
 			}
 		};
-		self.score += move_score;
 		move_score
 	}
 
 	fn perform_linear_move(
-		&mut self,
+		board: &mut Grid,
 		direction: (isize, isize),
 		tile_pos: (usize, usize),
+		merge_mode: MergeMode,
+		merged: &mut Vec<(usize, usize)>,
 	) -> usize {
-		if self
-			.board
+		if board
 			.get(tile_pos)
 			.expect("function should only be called internally with known coordinates")
 			.is_empty()
 		{
 			0
 		} else {
-			let mut displacement = Displacement::new(&mut self.board, tile_pos, direction);
+			let mut displacement = Displacement::new(board, tile_pos, direction)
+				.merge_once(merge_mode == MergeMode::Standard, merged);
 			displacement.move_all();
 			displacement.pop_score()
 		}
@@ -191,6 +885,7 @@ pub struct Displacement<'g> {
 	position: (usize, usize),
 	direction: (isize, isize),
 	score: usize,
+	merged: Option<&'g mut Vec<(usize, usize)>>,
 }
 
 impl<'g> Displacement<'g> {
@@ -200,9 +895,19 @@ impl<'g> Displacement<'g> {
 			position,
 			direction,
 			score: 0,
+			merged: None,
 		}
 	}
 
+	///
+	/// prevent the tile from merging into one of the `merged` positions and record the position it merges into,
+	/// keeps the cascading behaviour when `enabled` is false
+	///
+	pub fn merge_once(mut self, enabled: bool, merged: &'g mut Vec<(usize, usize)>) -> Self {
+		self.merged = if enabled { Some(merged) } else { None };
+		self
+	}
+
 	pub fn pop_score(self) -> usize {
 		let Displacement { score, .. } = self;
 		score
@@ -230,10 +935,13 @@ impl<'g> Displacement<'g> {
 					self.set_pos(next_pos);
 					true
 				}
-				Some(value) if value == current_value => {
+				Some(value) if value == current_value && !self.already_merged(next_pos) => {
 					self.grid.move_tile(current_pos, next_pos);
 					self.grid.set(next_pos, Some(value * 2));
 					self.score = value * 2;
+					if let Some(merged) = &mut self.merged {
+						merged.push(next_pos);
+					}
 					false
 				}
 				Some(_) => false,
@@ -246,8 +954,8 @@ impl<'g> Displacement<'g> {
 	fn get_next_pos(&self) -> Option<(usize, usize)> {
 		let (current_x, current_y) = self.position;
 		let (dx, dy) = self.direction;
-		if would_overflow(current_x, dx, self.grid.size() - 1)
-			|| would_overflow(current_y, dy, self.grid.size() - 1)
+		if would_overflow(current_x, dx, self.grid.width() - 1)
+			|| would_overflow(current_y, dy, self.grid.height() - 1)
 		{
 			None
 		} else {
@@ -257,6 +965,13 @@ impl<'g> Displacement<'g> {
 		}
 	}
 
+	fn already_merged(&self, position: (usize, usize)) -> bool {
+		match &self.merged {
+			Some(merged) => merged.contains(&position),
+			None => false,
+		}
+	}
+
 	fn set_pos(&mut self, (x, y): (usize, usize)) {
 		self.position = (x, y);
 	}
@@ -268,3 +983,123 @@ fn would_overflow(number: usize, delta: isize, max: usize) -> bool {
 	let too_big = number == max && delta == 1;
 	too_little || too_big
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{Game, MergeMode, Rules, SpawnTable};
+	use crate::{controller::Move, grid::Grid};
+
+	/// play turns with the first legal move, starting the search from a different move every turn
+	fn play(game: &mut Game, turns: usize) {
+		for _ in 0..turns {
+			let moves = Move::all();
+			let start = game.get_turn_index() % moves.len();
+			let movement = (0..moves.len())
+				.map(|offset| moves[(start + offset) % moves.len()])
+				.find(|&movement| game.is_legal(movement))
+				.expect("the game is not lost yet");
+			game.turn(movement).expect("the move is legal");
+		}
+	}
+
+	/// values of a single row moved left
+	fn move_row_left(row: &[usize], merge_mode: MergeMode) -> Vec<Option<usize>> {
+		let mut grid = Grid::new(row.len(), 1);
+		for (x, &value) in row.iter().enumerate() {
+			grid.set((x, 0), Some(value));
+		}
+		Game::move_board(&mut grid, Move::LEFT, merge_mode);
+		(0..row.len()).map(|x| grid.get_val((x, 0))).collect()
+	}
+
+	#[test]
+	fn tiles_merge_once_per_move_unless_cascading() {
+		let standard = move_row_left(&[1, 1, 2], MergeMode::Standard);
+		assert_eq!(standard, [Some(2), Some(2), None]);
+		let cascading = move_row_left(&[1, 1, 2], MergeMode::Cascading);
+		assert_eq!(cascading, [Some(4), None, None]);
+	}
+
+	#[test]
+	fn spawn_tables_are_parsed() {
+		let table: SpawnTable = "2:9,4:1".parse().expect("the table is valid");
+		assert_eq!(table.entries(), [(2, 9), (4, 1)]);
+		assert_eq!(table, SpawnTable::classic());
+		assert_eq!("ones".parse(), Ok(SpawnTable::ones()));
+		// entries without weight are left out
+		let table: SpawnTable = "1:1,2:0".parse().expect("the table is valid");
+		assert_eq!(table.entries(), [(1, 1)]);
+	}
+
+	#[test]
+	fn invalid_spawn_tables_are_rejected() {
+		let oversized = format!("{}:1", usize::MAX / 512);
+		let tables = [
+			"2-9",
+			"0:1",
+			"2:0,4:0",
+			"2:4294967295,4:1",
+			oversized.as_str(),
+		];
+		for table in tables {
+			assert!(
+				table.parse::<SpawnTable>().is_err(),
+				"'{table}' is accepted"
+			);
+		}
+	}
+
+	#[test]
+	fn same_seed_and_moves_give_the_same_game() {
+		let rules = Rules::default().seed(Some(7));
+		let mut first = Game::new(rules.clone());
+		let mut second = Game::new(rules);
+		assert!(first.get_board() == second.get_board());
+		play(&mut first, 40);
+		play(&mut second, 40);
+		assert!(first.get_board() == second.get_board());
+		assert_eq!(first.get_score(), second.get_score());
+	}
+
+	#[test]
+	fn a_loaded_game_continues_like_the_saved_one() {
+		let mut game = Game::new(Rules::default().seed(Some(11)));
+		play(&mut game, 20);
+		let path = std::env::temp_dir().join(format!("rs48-save-test-{}.json", std::process::id()));
+		game.save(&path).expect("the save file is writable");
+		let loaded = Game::load(&path);
+		std::fs::remove_file(&path).ok();
+		let mut loaded = loaded.expect("the save file is readable");
+		assert!(loaded.get_board() == game.get_board());
+		assert_eq!(loaded.get_score(), game.get_score());
+		assert_eq!(loaded.get_turn_index(), game.get_turn_index());
+		// the spawns after the save are the same too
+		play(&mut game, 20);
+		play(&mut loaded, 20);
+		assert!(loaded.get_board() == game.get_board());
+		assert_eq!(loaded.get_score(), game.get_score());
+	}
+
+	#[test]
+	fn undo_and_redo_restore_the_whole_state() {
+		let mut game = Game::new(Rules::default().seed(Some(3)).target_tile(Some(8)));
+		while !game.reached_target() {
+			play(&mut game, 1);
+		}
+		let (score, turn_index) = (game.get_score(), game.get_turn_index());
+		let won_board = game.get_board().clone();
+		game.keep_going();
+
+		assert!(game.undo());
+		assert!(!game.keep_going);
+		assert_eq!(game.get_turn_index(), turn_index - 1);
+		assert!(game.get_score() < score);
+		assert!(game.get_board() != &won_board);
+
+		assert!(game.redo());
+		assert!(game.keep_going);
+		assert_eq!(game.get_turn_index(), turn_index);
+		assert_eq!(game.get_score(), score);
+		assert!(game.get_board() == &won_board);
+	}
+}
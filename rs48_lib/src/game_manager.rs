@@ -1,18 +1,32 @@
-use std::{thread, time::Duration};
+use std::{
+	io::{self, Write},
+	path::PathBuf,
+	thread,
+	time::Duration,
+};
 
 use crate::{
-	controller::Controller,
-	game::{self, Game, GameError},
+	controller::{Action, Controller, ControllerError},
+	game::{self, Game, GameError, GameStatus},
+	grid_displayer::GridDisplayer,
+	observer::Observer,
+	renderer::{AnsiRenderer, Renderer},
+	replay::Replay,
+	screen::Screen,
+	turn_outcome::TurnOutcome,
 };
 
-use super::{clear_term, grid_displayer::GridDisplayer};
-
 pub struct Rules {
 	display: bool,
 	display_skips: usize,
 	clear_term: bool,
 	color_seed: u16,
 	turn_duration: Duration,
+	replay_file: Option<PathBuf>,
+	save_file: Option<PathBuf>,
+	renderer: Option<Box<dyn Renderer>>,
+	/// the standard output when there is none
+	output: Option<Box<dyn Write>>,
 }
 
 impl Rules {
@@ -28,13 +42,15 @@ impl Rules {
 		self
 	}
 
-	/// wether to clear the terminal or not between displays
+	/// wether to refresh the game in place on the alternate screen of the terminal,
+	/// or to write every display after the previous one, only the standard output
+	/// is refreshed in place and only when it is a terminal
 	pub fn clear_term(mut self, clear_term: bool) -> Self {
 		self.clear_term = clear_term;
 		self
 	}
 
-	/// seed for the procedural coloration of tiles
+	/// seed for the procedural coloration of tiles of the default renderer
 	pub fn color_seed(mut self, color_seed: u16) -> Self {
 		self.color_seed = color_seed;
 		self
@@ -45,6 +61,30 @@ impl Rules {
 		self.turn_duration = turn_duration;
 		self
 	}
+
+	/// file the game is recorded to once it ends, see [`Replay`]
+	pub fn replay_file(mut self, replay_file: Option<PathBuf>) -> Self {
+		self.replay_file = replay_file;
+		self
+	}
+
+	/// file the game is written to when the controller saves it
+	pub fn save_file(mut self, save_file: Option<PathBuf>) -> Self {
+		self.save_file = save_file;
+		self
+	}
+
+	/// draws the game, an [`AnsiRenderer`] by default
+	pub fn renderer(mut self, renderer: Box<dyn Renderer>) -> Self {
+		self.renderer = Some(renderer);
+		self
+	}
+
+	/// where the game is drawn, the standard output by default, every display is written after the previous one
+	pub fn output(mut self, output: impl Write + 'static) -> Self {
+		self.output = Some(Box::new(output));
+		self
+	}
 }
 
 impl Default for Rules {
@@ -55,6 +95,10 @@ impl Default for Rules {
 			clear_term: true,
 			color_seed: 35,
 			turn_duration: Duration::ZERO,
+			replay_file: None,
+			save_file: None,
+			renderer: None,
+			output: None,
 		}
 	}
 }
@@ -62,12 +106,19 @@ impl Default for Rules {
 pub struct GameManager {
 	game: Game,
 	controller: Box<dyn Controller>,
-	grid_displayer: GridDisplayer,
+	renderer: Box<dyn Renderer>,
+	output: Box<dyn Write>,
+	/// what happened during the last turn, until the next render
+	last_outcome: Option<TurnOutcome>,
 	display_to_skip: usize,
 	display: bool,
 	display_skips: usize,
-	clear_term: bool,
 	turn_duration: Duration,
+	replay: Option<(PathBuf, Replay)>,
+	save_file: Option<PathBuf>,
+	/// message shown under the grid on the next display
+	notice: Option<String>,
+	observers: Vec<Box<dyn Observer>>,
 }
 
 impl GameManager {
@@ -76,72 +127,255 @@ impl GameManager {
 		manager_rules: self::Rules,
 		controller: Box<dyn Controller>,
 	) -> Self {
-		let game = Game::new(game_rules);
+		Self::with_game(Game::new(game_rules), manager_rules, controller)
+	}
+
+	///
+	/// constructor managing a game that already started, like a loaded one
+	///
+	pub fn with_game(
+		game: Game,
+		manager_rules: self::Rules,
+		controller: Box<dyn Controller>,
+	) -> Self {
 		let Rules {
 			clear_term,
 			color_seed,
 			display,
 			display_skips,
 			turn_duration,
+			replay_file,
+			save_file,
+			renderer,
+			output,
 		} = manager_rules;
-		let grid_displayer = GridDisplayer::new(color_seed);
+		let renderer = renderer
+			.unwrap_or_else(|| AnsiRenderer::new(GridDisplayer::new(color_seed)).into_box());
+		let replay = replay_file.map(|path| (path, Replay::new(&game)));
+		let output: Box<dyn Write> = match output {
+			Some(output) => output,
+			None if clear_term && termion::is_tty(&io::stdout()) => {
+				Box::new(Screen::new(io::stdout()).terminal(true))
+			}
+			None => Box::new(io::stdout()),
+		};
 		Self {
 			game,
 			controller,
 			display_to_skip: 0,
 			display,
 			display_skips,
-			clear_term,
 			turn_duration,
-			grid_displayer,
+			renderer,
+			output,
+			last_outcome: None,
+			replay,
+			save_file,
+			notice: None,
+			observers: vec![],
 		}
 	}
 
+	pub fn get_game(&self) -> &Game {
+		&self.game
+	}
+
+	///
+	/// register an observer notified of what happens in the game from now on
+	///
+	pub fn observe(&mut self, observer: impl Observer + 'static) {
+		self.observers.push(observer.into_box());
+	}
+
 	pub fn turn(&mut self) -> Result<(), GameError> {
-		self.display_conditionnally();
+		self.display_conditionnally()?;
 		self.game_turn()?;
 		thread::sleep(self.turn_duration);
 		Ok(())
 	}
 
-	fn display_conditionnally(&mut self) {
+	fn display_conditionnally(&mut self) -> io::Result<()> {
 		if self.display {
 			if self.display_to_skip == 0 {
-				if self.clear_term {
-					clear_term();
+				self.print_display()?;
+				if let Some(notice) = self.notice.take() {
+					self.renderer.message(&mut self.output, &notice)?;
 				}
-				self.print_display();
+				self.output.flush()?;
 				self.display_to_skip = self.display_skips;
 			} else {
 				self.display_to_skip -= 1;
 			}
 		}
+		Ok(())
 	}
 
 	fn game_turn(&mut self) -> Result<(), GameError> {
-		let movement = self.controller.next_move(&self.game)?;
-		self.game.turn(movement)?;
-		Ok(())
+		let replay = self.replay.as_mut().map(|(_, replay)| replay);
+		let action = self.controller.next_action(&self.game)?;
+		if let Action::Undo | Action::Redo = action {
+			self.last_outcome = None;
+		}
+		match action {
+			Action::Move(movement) => {
+				// refused moves are not announced, they change nothing
+				if self.game.is_legal(movement) {
+					for observer in &mut self.observers {
+						observer.before_move(&self.game, movement);
+					}
+				}
+				// only the renderer looks at the slides and merges of the move
+				let result = if self.display {
+					self.game.turn(movement)
+				} else {
+					self.game.quick_turn(movement)
+				};
+				match result {
+					Ok(outcome) => {
+						if let Some(replay) = replay {
+							replay.record(&outcome);
+						}
+						for observer in &mut self.observers {
+							observer.after_move(&self.game, movement, outcome.score);
+							for &spawn in &outcome.spawns {
+								observer.spawn(&self.game, spawn);
+							}
+						}
+						self.last_outcome = Some(outcome);
+						Ok(())
+					}
+					// refused moves are simply asked again to players, automated controllers would repeat them forever
+					Err(GameError::InvalidMove) if self.controller.interactive() => Ok(()),
+					Err(error) => Err(error),
+				}
+			}
+			Action::Undo => {
+				self.undo();
+				Ok(())
+			}
+			Action::Redo => {
+				if let (true, Some(replay)) = (self.game.redo(), replay) {
+					replay.redo();
+				}
+				Ok(())
+			}
+			Action::Save => {
+				self.save();
+				Ok(())
+			}
+		}
+	}
+
+	///
+	/// write the game to the save file, the result is shown on the next display,
+	/// a failed save is only reported so that the game goes on
+	///
+	fn save(&mut self) {
+		self.notice = Some(match &self.save_file {
+			Some(path) => match self.game.save(path) {
+				Ok(()) => format!("game saved to '{}'", path.display()),
+				Err(error) => format!("failed to save to '{}': {error}", path.display()),
+			},
+			None => "no save file to save the game to".to_string(),
+		});
 	}
 
-	pub fn print_display(&self) {
-		let headline_display = self.get_headline_display();
-		println!("{headline_display}");
-		let grid = self.game.get_board();
-		let grid_display = self.grid_displayer.display(grid);
-		println!("{grid_display}");
+	/// go back a turn, in the replay too
+	fn undo(&mut self) {
+		let replay = self.replay.as_mut().map(|(_, replay)| replay);
+		if let (true, Some(replay)) = (self.game.undo(), replay) {
+			replay.undo();
+		}
 	}
 
-	fn get_headline_display(&self) -> String {
-		let score = self.game.get_score();
-		let turn = self.game.get_turn_index();
-		let biggest_tile = self.game.get_board().biggest_value();
-		format!("score: {score:>12} | biggest tile: {biggest_tile:>12} | turn: {turn:>12}")
+	///
+	/// draw the game with the renderer, on the alternate screen it shows once the output is flushed
+	///
+	pub fn print_display(&mut self) -> io::Result<()> {
+		let outcome = self.last_outcome.take();
+		self.renderer
+			.render(&mut self.output, &self.game, outcome.as_ref())
 	}
 
+	///
+	/// play turns until the game is lost or the controller stops after a victory,
+	/// then write the replay if there is one, even when the game stopped on an error
+	///
 	pub fn play_all(&mut self) -> Result<(), GameError> {
+		let result = self.play_turns();
+		if let Some((path, replay)) = &self.replay {
+			replay.save(path)?;
+		}
+		result
+	}
+
+	fn play_turns(&mut self) -> Result<(), GameError> {
+		for observer in &mut self.observers {
+			observer.game_start(&self.game);
+		}
+		// observers see the end of the game even when it stops on a quit or an error
+		let result = self.play_until_over();
+		for observer in &mut self.observers {
+			observer.game_over(&self.game);
+		}
+		result
+	}
+
+	fn play_until_over(&mut self) -> Result<(), GameError> {
 		loop {
-			self.turn()?;
+			match self.game.status() {
+				GameStatus::Ongoing => match self.turn() {
+					// quitting during the game stops it like quitting at a prompt
+					Err(GameError::ControllerError(ControllerError::ExitSignal)) => break,
+					result => result?,
+				},
+				GameStatus::Won => {
+					// only players choose to keep going, other controllers answer right away
+					let message = if self.controller.interactive() {
+						"victory! press 'c' to keep going or 'q' to quit"
+					} else {
+						"victory!"
+					};
+					self.display_message(message)?;
+					if !self.controller.keep_going(&self.game)? {
+						break;
+					}
+					self.game.keep_going();
+				}
+				GameStatus::Lost => {
+					// players can take back the move that lost the game
+					let can_undo = self.game.get_history().undo_count() > 0;
+					if !self.controller.interactive() || !can_undo {
+						self.display_message("game over")?;
+						break;
+					}
+					self.display_message("game over! press 'u' to undo or 'q' to quit")?;
+					match self.controller.next_action(&self.game) {
+						Ok(Action::Undo) => {
+							self.last_outcome = None;
+							self.undo();
+						}
+						Ok(Action::Save) => self.save(),
+						// nothing else changes a lost game, the prompt is shown again
+						Ok(Action::Move(_) | Action::Redo) => (),
+						Err(ControllerError::ExitSignal) => break,
+						Err(error) => return Err(error.into()),
+					}
+				}
+			}
 		}
+		Ok(())
+	}
+
+	fn display_message(&mut self, message: &str) -> io::Result<()> {
+		if self.display {
+			self.print_display()?;
+			self.renderer.message(&mut self.output, message)?;
+			if let Some(notice) = self.notice.take() {
+				self.renderer.message(&mut self.output, &notice)?;
+			}
+			self.output.flush()?;
+		}
+		Ok(())
 	}
 }
@@ -1,4 +1,6 @@
-#[derive(Clone, Copy)]
+use super::bitboard::BitBoard;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Tile {
 	value: Option<usize>,
 }
@@ -20,41 +22,81 @@ impl Tile {
 	}
 }
 
+/// how the tiles of a grid are stored
+#[derive(Clone)]
+enum Tiles {
+	/// one tile per cell, for any size and value
+	Rows(Vec<Vec<Tile>>),
+	/// 4×4 grid packed in a single number, cheap to copy and to move
+	Packed(BitBoard),
+}
+
 #[derive(Clone)]
 pub struct Grid {
-	size: usize,
-	tiles: Vec<Vec<Tile>>,
+	width: usize,
+	height: usize,
+	tiles: Tiles,
 }
 
 impl Grid {
 	///
 	/// constructor
 	///
-	pub fn new(size: usize) -> Self {
-		let tiles = (0..size)
-			.map(|_| (0..size).map(|_| Tile::new_empty()).collect())
+	pub fn new(width: usize, height: usize) -> Self {
+		let tiles = (0..height)
+			.map(|_| (0..width).map(|_| Tile::new_empty()).collect())
 			.collect();
-		Self { size, tiles }
+		Self {
+			width,
+			height,
+			tiles: Tiles::Rows(tiles),
+		}
+	}
+
+	///
+	/// constructor of a 4×4 grid stored as a [`BitBoard`], `unit` is the value of the smallest tile
+	///
+	pub fn new_packed(unit: usize) -> Self {
+		Self {
+			width: BitBoard::SIZE,
+			height: BitBoard::SIZE,
+			tiles: Tiles::Packed(BitBoard::new(unit)),
+		}
 	}
 
 	///
 	/// set the value of the tile at the selected position
 	///
 	pub fn set(&mut self, (x, y): (usize, usize), value: Option<usize>) {
-		self.tiles[y][x] = if let Some(value) = value {
-			Tile::new_with_value(value)
-		} else {
-			Tile::new_empty()
-		};
+		if let Tiles::Packed(packed) = &mut self.tiles {
+			match packed.encode(value) {
+				Some(exponent) => return packed.set_exponent((x, y), exponent),
+				// the value does not fit in the packed grid, fallback to rows
+				None => self.unpack(),
+			}
+		}
+		if let Tiles::Rows(rows) = &mut self.tiles {
+			rows[y][x] = if let Some(value) = value {
+				Tile::new_with_value(value)
+			} else {
+				Tile::new_empty()
+			};
+		}
 	}
 
 	///
 	/// get a tile if the position is in the grid
 	///
-	pub fn get(&self, (x, y): (usize, usize)) -> Option<&Tile> {
-		match self.tiles.get(y).map(|row| row.get(x)) {
-			Some(Some(tile)) => Some(tile),
-			_ => None,
+	pub fn get(&self, (x, y): (usize, usize)) -> Option<Tile> {
+		if x >= self.width || y >= self.height {
+			return None;
+		}
+		match &self.tiles {
+			Tiles::Rows(rows) => Some(rows[y][x]),
+			Tiles::Packed(packed) => Some(match packed.get((x, y)) {
+				Some(value) => Tile::new_with_value(value),
+				None => Tile::new_empty(),
+			}),
 		}
 	}
 
@@ -69,40 +111,120 @@ impl Grid {
 	}
 
 	///
-	/// get the size of the grid
+	/// get the number of columns of the grid
+	///
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	///
+	/// get the number of rows of the grid
 	///
-	pub fn size(&self) -> usize {
-		self.size
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	///
+	/// get the position of every cell, row by row
+	///
+	pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+		let width = self.width;
+		(0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
 	}
 
 	///
 	/// get the array of tiles
 	///
-	pub fn tiles(&self) -> &Vec<Vec<Tile>> {
-		&self.tiles
+	pub fn tiles(&self) -> Vec<Vec<Tile>> {
+		match &self.tiles {
+			Tiles::Rows(rows) => rows.clone(),
+			Tiles::Packed(_) => (0..self.height)
+				.map(|y| {
+					(0..self.width)
+						.map(|x| self.get((x, y)).expect("coordinates are valid"))
+						.collect()
+				})
+				.collect(),
+		}
+	}
+
+	///
+	/// get the packed representation of the grid if it uses one
+	///
+	pub fn packed_mut(&mut self) -> Option<&mut BitBoard> {
+		match &mut self.tiles {
+			Tiles::Packed(packed) => Some(packed),
+			Tiles::Rows(_) => None,
+		}
+	}
+
+	///
+	/// store the tiles as rows from now on, to hold any value
+	///
+	pub fn unpack(&mut self) {
+		if let Tiles::Packed(_) = self.tiles {
+			self.tiles = Tiles::Rows(self.tiles());
+		}
 	}
 
 	///
 	/// move a tile over another one, replace the previously occupied place by an empty tile and overrides the destination
 	///
 	pub fn move_tile(&mut self, (src_x, src_y): (usize, usize), (dst_x, dst_y): (usize, usize)) {
-		let src = self.tiles[src_y][src_x];
-		self.tiles[dst_y][dst_x] = src;
-		self.tiles[src_y][src_x] = Tile::new_empty();
+		let src = self.get_val((src_x, src_y));
+		self.set((dst_x, dst_y), src);
+		self.set((src_x, src_y), None);
 	}
 
 	///
 	/// get the biggest value of the board
 	///
 	pub fn biggest_value(&self) -> usize {
-		self.tiles()
-			.iter()
-			.filter_map(|row| {
-				row.iter()
-					.filter_map(|tile| tile.value())
-					.reduce(|a, b| a.max(b))
-			})
-			.reduce(|a, b| a.max(b))
+		self.positions()
+			.filter_map(|position| self.get_val(position))
+			.max()
 			.unwrap_or(0)
 	}
 }
+
+impl PartialEq for Grid {
+	fn eq(&self, other: &Self) -> bool {
+		match (&self.tiles, &other.tiles) {
+			(Tiles::Packed(a), Tiles::Packed(b)) => a == b,
+			(Tiles::Rows(a), Tiles::Rows(b)) => a == b,
+			_ => self.tiles() == other.tiles(),
+		}
+	}
+}
+
+impl Eq for Grid {}
+
+#[cfg(test)]
+impl Grid {
+	///
+	/// get the value of every cell, row by row
+	///
+	pub(crate) fn values(&self) -> Vec<Option<usize>> {
+		self.positions()
+			.map(|position| self.get_val(position))
+			.collect()
+	}
+
+	///
+	/// fill most cells with `unit` times a power of two below `2^exponents`,
+	/// few distinct values make moves merge often
+	///
+	pub(crate) fn fill_randomly(
+		&mut self,
+		rng: &mut impl rand::Rng,
+		unit: usize,
+		exponents: usize,
+	) {
+		for position in self.positions().collect::<Vec<_>>() {
+			let value = rng
+				.gen_bool(0.7)
+				.then(|| unit << rng.gen_range(0..exponents));
+			self.set(position, value);
+		}
+	}
+}
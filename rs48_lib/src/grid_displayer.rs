@@ -1,44 +1,137 @@
 use std::{
 	collections::hash_map::DefaultHasher,
+	fmt::Display,
 	hash::{Hash, Hasher},
 	mem::transmute_copy,
+	str::FromStr,
 };
 
 use termion::color;
 
-use super::grid::{Grid, Tile};
+use super::{
+	color_support::ColorSupport,
+	grid::{Grid, Tile},
+	theme::Theme,
+};
+
+/// how the values of tiles are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueFormat {
+	/// every digit, `131072`
+	Full,
+	/// SI suffixes above 9999, `131k`
+	Si,
+	/// powers of two as an exponent, `2^17`
+	Exponent,
+	/// only the logarithm in base 2, `17`
+	Log2,
+}
+
+impl ValueFormat {
+	/// write a value, values that are not powers of two are written in full by the exponent formats
+	pub fn format(&self, value: usize) -> String {
+		let exponent = value.is_power_of_two().then(|| value.trailing_zeros());
+		match (self, exponent) {
+			(Self::Si, _) if value >= 10_000 => {
+				let mut mantissa = value as f64;
+				let mut suffixes = ["k", "M", "G", "T", "P", "E"].into_iter();
+				let mut suffix = "";
+				while mantissa >= 1000. {
+					mantissa /= 1000.;
+					suffix = suffixes.next().unwrap_or(suffix);
+				}
+				if mantissa < 10. {
+					format!("{:.1}{suffix}", (mantissa * 10.).floor() / 10.)
+				} else {
+					format!("{}{suffix}", mantissa.floor())
+				}
+			}
+			(Self::Exponent, Some(exponent)) => format!("2^{exponent}"),
+			(Self::Log2, Some(exponent)) => exponent.to_string(),
+			_ => value.to_string(),
+		}
+	}
+}
+
+impl Display for ValueFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::Full => "full",
+			Self::Si => "si",
+			Self::Exponent => "exponent",
+			Self::Log2 => "log2",
+		})
+	}
+}
+
+impl FromStr for ValueFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"full" => Ok(Self::Full),
+			"si" => Ok(Self::Si),
+			"exponent" => Ok(Self::Exponent),
+			"log2" => Ok(Self::Log2),
+			_ => Err(format!("failed to parse '{s}' into a 'ValueFormat'")),
+		}
+	}
+}
 
 pub struct TileDisplayer {
 	color_seed: u16,
+	value_format: ValueFormat,
+	tile_length: Option<usize>,
+	theme: Option<Theme>,
+	color_support: ColorSupport,
 }
 
 impl TileDisplayer {
 	pub fn new(color_seed: u16) -> Self {
-		Self { color_seed }
+		Self {
+			color_seed,
+			value_format: ValueFormat::Full,
+			tile_length: None,
+			theme: None,
+			color_support: ColorSupport::TrueColor,
+		}
 	}
 
 	const TILE_LENGTH: usize = 7;
 	const TILE_HEIGHT: usize = 3;
+	/// room for the corners of number tiles
+	const MIN_TILE_LENGTH: usize = 4;
+
+	///
+	/// get the width of the tiles of a grid, the one set by the user
+	/// or one that fits the longest value on the grid with a space on both sides
+	///
+	pub fn length(&self, grid: &Grid) -> usize {
+		match self.tile_length {
+			Some(length) => length.max(Self::MIN_TILE_LENGTH),
+			None => grid
+				.positions()
+				.filter_map(|position| grid.get_val(position))
+				.map(|value| self.value_format.format(value).len() + 2)
+				.fold(Self::TILE_LENGTH, usize::max),
+		}
+	}
 
-	pub fn display(&self, tile: &Tile) -> String {
+	pub fn display(&self, tile: &Tile, length: usize) -> String {
 		match tile.value() {
-			Some(value) => {
-				Self::color_representation(Self::display_number(value), value, self.color_seed)
-			}
-			None => [
-				// empty tile
-				"       ", "       ", "       ",
-			]
-			.join("\n"),
+			Some(value) => self.color_representation(self.display_number(value, length), value),
+			// empty tile
+			None => vec![" ".repeat(length); Self::TILE_HEIGHT].join("\n"),
 		}
 	}
 
-	fn display_number(value: usize) -> String {
+	fn display_number(&self, value: usize, length: usize) -> String {
+		let gap = " ".repeat(length - Self::MIN_TILE_LENGTH);
 		[
 			// number tile
-			"┌─   ─┐",
-			&Self::pad_both(value.to_string(), Self::TILE_LENGTH),
-			"└─   ─┘",
+			format!("┌─{gap}─┐"),
+			Self::pad_both(self.value_format.format(value), length),
+			format!("└─{gap}─┘"),
 		]
 		.join("\n")
 	}
@@ -55,10 +148,21 @@ impl TileDisplayer {
 		}
 	}
 
-	fn color_representation(text: String, value: usize, color_seed: u16) -> String {
-		let color = Self::hashed_color(value, color_seed);
-		let color_code = color::Bg(color);
-		let reset_code = color::Bg(color::Reset);
+	/// colors of the theme when there is one, a background hashed from the value otherwise
+	fn color_representation(&self, text: String, value: usize) -> String {
+		let color_code = match &self.theme {
+			Some(theme) => {
+				let colors = theme.colors(value);
+				let background = self.color_support.background(colors.background);
+				let foreground = self.color_support.foreground(colors.foreground);
+				format!("{background}{foreground}")
+			}
+			None => {
+				let color::Rgb(r, g, b) = Self::hashed_color(value, self.color_seed);
+				self.color_support.background((r, g, b))
+			}
+		};
+		let reset_code = self.color_support.reset();
 
 		let text = text
 			.split('\n')
@@ -105,6 +209,30 @@ impl GridDisplayer {
 		Self { tile_displayer }
 	}
 
+	/// how values are written on tiles
+	pub fn value_format(mut self, value_format: ValueFormat) -> Self {
+		self.tile_displayer.value_format = value_format;
+		self
+	}
+
+	/// width of tiles in characters, values that do not fit are cut, sized to fit the values when none is given
+	pub fn tile_length(mut self, tile_length: Option<usize>) -> Self {
+		self.tile_displayer.tile_length = tile_length;
+		self
+	}
+
+	/// colors of the tiles, replaces the colors hashed from the color seed
+	pub fn theme(mut self, theme: Option<Theme>) -> Self {
+		self.tile_displayer.theme = theme;
+		self
+	}
+
+	/// colors the terminal can show, the ones of the tiles are replaced by the closest available
+	pub fn color_support(mut self, color_support: ColorSupport) -> Self {
+		self.tile_displayer.color_support = color_support;
+		self
+	}
+
 	/// (0: '┘'), (1: '┐'), (2: '┌'), (3: '└'), (4: '┼'), (5: '─'), (6: '├'), (7: '┤'), (8: '┴'), (9: '┬'), (10: '│')
 	const DISPLAY_CHAR: [&'static str; 11] =
 		["┘", "┐", "┌", "└", "┼", "─", "├", "┤", "┴", "┬", "│"];
@@ -113,12 +241,13 @@ impl GridDisplayer {
 	/// returns a string of multiple lines representing the grid
 	///
 	pub fn display(&self, grid: &Grid) -> String {
+		let length = self.tile_displayer.length(grid);
 		let tiles: Vec<Vec<_>> = grid
 			.tiles()
 			.iter()
 			.map(|row| {
 				row.iter()
-					.map(|tile| self.tile_displayer.display(tile))
+					.map(|tile| self.tile_displayer.display(tile, length))
 					.collect()
 			})
 			.collect();
@@ -132,7 +261,6 @@ impl GridDisplayer {
 				for item_representation in row_representation {
 					item_representation
 						.split('\n')
-						.into_iter()
 						.zip(row_lines.iter_mut())
 						.for_each(|(item_line, row_line)| row_line.push(item_line.to_string()));
 				}
@@ -147,24 +275,24 @@ impl GridDisplayer {
 			.collect();
 
 		[
-			self.first_grid_display_line(grid),
-			row_representations.join(&self.between_grid_display_line(grid)),
-			self.last_grid_display_line(grid),
+			self.first_grid_display_line(grid, length),
+			row_representations.join(&self.between_grid_display_line(grid, length)),
+			self.last_grid_display_line(grid, length),
 		]
 		.join("\n")
 	}
 
-	fn first_grid_display_line(&self, grid: &Grid) -> String {
-		let middle = (0..grid.size())
-			.map(|_| Self::DISPLAY_CHAR[5].repeat(TileDisplayer::TILE_LENGTH))
+	fn first_grid_display_line(&self, grid: &Grid, length: usize) -> String {
+		let middle = (0..grid.width())
+			.map(|_| Self::DISPLAY_CHAR[5].repeat(length))
 			.collect::<Vec<_>>()
 			.join(Self::DISPLAY_CHAR[9]);
 		[Self::DISPLAY_CHAR[2], &middle, Self::DISPLAY_CHAR[1]].join("")
 	}
 
-	fn between_grid_display_line(&self, grid: &Grid) -> String {
-		let middle = (0..grid.size())
-			.map(|_| Self::DISPLAY_CHAR[5].repeat(TileDisplayer::TILE_LENGTH))
+	fn between_grid_display_line(&self, grid: &Grid, length: usize) -> String {
+		let middle = (0..grid.width())
+			.map(|_| Self::DISPLAY_CHAR[5].repeat(length))
 			.collect::<Vec<_>>()
 			.join(Self::DISPLAY_CHAR[4]);
 		[
@@ -177,11 +305,39 @@ impl GridDisplayer {
 		.join("")
 	}
 
-	fn last_grid_display_line(&self, grid: &Grid) -> String {
-		let middle = (0..grid.size())
-			.map(|_| Self::DISPLAY_CHAR[5].repeat(TileDisplayer::TILE_LENGTH))
+	fn last_grid_display_line(&self, grid: &Grid, length: usize) -> String {
+		let middle = (0..grid.width())
+			.map(|_| Self::DISPLAY_CHAR[5].repeat(length))
 			.collect::<Vec<_>>()
 			.join(Self::DISPLAY_CHAR[8]);
 		[Self::DISPLAY_CHAR[3], &middle, Self::DISPLAY_CHAR[0], "\n"].join("")
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::ValueFormat;
+
+	#[test]
+	fn values_are_formatted() {
+		let cases = [
+			(ValueFormat::Full, 131072, "131072"),
+			(ValueFormat::Si, 9999, "9999"),
+			(ValueFormat::Si, 10_000, "10k"),
+			(ValueFormat::Si, 131072, "131k"),
+			(ValueFormat::Si, 999_999, "999k"),
+			(ValueFormat::Si, 1_000_000, "1.0M"),
+			(ValueFormat::Si, 1_500_000, "1.5M"),
+			(ValueFormat::Si, 1_999_999, "1.9M"),
+			(ValueFormat::Si, 1 << 40, "1.0T"),
+			(ValueFormat::Exponent, 1, "2^0"),
+			(ValueFormat::Exponent, 131072, "2^17"),
+			(ValueFormat::Exponent, 12, "12"),
+			(ValueFormat::Log2, 2048, "11"),
+			(ValueFormat::Log2, 12, "12"),
+		];
+		for (format, value, expected) in cases {
+			assert_eq!(format.format(value), expected, "{value} in {format}");
+		}
+	}
+}
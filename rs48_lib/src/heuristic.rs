@@ -0,0 +1,245 @@
+use std::{error::Error, fmt::Display, fs, io, path::Path, str::FromStr};
+
+use super::grid::Grid;
+
+///
+/// evaluation of a grid for search based controllers, higher is better
+///
+pub trait Heuristic: Send + Sync {
+	fn evaluate(&self, grid: &Grid) -> f64;
+
+	fn into_box(self) -> Box<dyn Heuristic>
+	where
+		Self: Sized + 'static,
+	{
+		Box::new(self)
+	}
+}
+
+impl<F> Heuristic for F
+where
+	F: Fn(&Grid) -> f64 + Send + Sync,
+{
+	fn evaluate(&self, grid: &Grid) -> f64 {
+		self(grid)
+	}
+}
+
+/// number of empty cells
+#[derive(Debug, Default)]
+pub struct EmptyCells;
+
+impl Heuristic for EmptyCells {
+	fn evaluate(&self, grid: &Grid) -> f64 {
+		grid.positions()
+			.filter(|&position| grid.get_val(position).is_none())
+			.count() as f64
+	}
+}
+
+/// how much every row and column only increases or only decreases, 0 when they all do
+#[derive(Debug, Default)]
+pub struct Monotonicity;
+
+impl Heuristic for Monotonicity {
+	fn evaluate(&self, grid: &Grid) -> f64 {
+		lines(grid)
+			.map(|line| {
+				let (mut increase, mut decrease) = (0., 0.);
+				for pair in line.windows(2) {
+					let difference = rank(pair[1]) - rank(pair[0]);
+					if difference > 0. {
+						increase += difference;
+					} else {
+						decrease -= difference;
+					}
+				}
+				-f64::min(increase, decrease)
+			})
+			.sum()
+	}
+}
+
+/// how close neighbouring tiles are in value, 0 when they are all equal
+#[derive(Debug, Default)]
+pub struct Smoothness;
+
+impl Heuristic for Smoothness {
+	fn evaluate(&self, grid: &Grid) -> f64 {
+		neighbours(grid)
+			.filter_map(|(a, b)| Some((grid.get_val(a)?, grid.get_val(b)?)))
+			.map(|(a, b)| -(rank(Some(a)) - rank(Some(b))).abs())
+			.sum()
+	}
+}
+
+/// 1 when the biggest tile is in a corner, 0 otherwise
+#[derive(Debug, Default)]
+pub struct MaxTileInCorner;
+
+impl Heuristic for MaxTileInCorner {
+	fn evaluate(&self, grid: &Grid) -> f64 {
+		let (right, bottom) = (grid.width() - 1, grid.height() - 1);
+		let biggest = grid.biggest_value();
+		let in_corner = [(0, 0), (right, 0), (0, bottom), (right, bottom)]
+			.into_iter()
+			.any(|corner| grid.get_val(corner) == Some(biggest));
+		if in_corner {
+			1.
+		} else {
+			0.
+		}
+	}
+}
+
+/// number of neighbouring tiles of the same value
+#[derive(Debug, Default)]
+pub struct MergePotential;
+
+impl Heuristic for MergePotential {
+	fn evaluate(&self, grid: &Grid) -> f64 {
+		neighbours(grid)
+			.filter(|&(a, b)| grid.get_val(a).is_some() && grid.get_val(a) == grid.get_val(b))
+			.count() as f64
+	}
+}
+
+///
+/// sum of features multiplied by their weight
+///
+pub struct WeightedHeuristic {
+	features: Vec<(f64, Box<dyn Heuristic>)>,
+}
+
+impl WeightedHeuristic {
+	///
+	/// constructor of a heuristic without features
+	///
+	pub fn new() -> Self {
+		Self { features: vec![] }
+	}
+
+	///
+	/// add a feature with its weight
+	///
+	pub fn with(mut self, weight: f64, feature: impl Heuristic + 'static) -> Self {
+		self.features.push((weight, Box::new(feature)));
+		self
+	}
+
+	///
+	/// read the weights of built-in features from a file, see [`WeightedHeuristic::from_str`]
+	///
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, WeightsError> {
+		fs::read_to_string(path)?.parse()
+	}
+
+	///
+	/// get a built-in feature from its name in weights files
+	///
+	pub fn feature(name: &str) -> Option<Box<dyn Heuristic>> {
+		match name {
+			"empty_cells" => Some(EmptyCells.into_box()),
+			"monotonicity" => Some(Monotonicity.into_box()),
+			"smoothness" => Some(Smoothness.into_box()),
+			"max_tile_in_corner" => Some(MaxTileInCorner.into_box()),
+			"merge_potential" => Some(MergePotential.into_box()),
+			_ => None,
+		}
+	}
+}
+
+impl Default for WeightedHeuristic {
+	fn default() -> Self {
+		Self::new()
+			.with(2.7, EmptyCells)
+			.with(1.0, Monotonicity)
+			.with(0.1, Smoothness)
+			.with(1.0, MaxTileInCorner)
+			.with(0.5, MergePotential)
+	}
+}
+
+impl Heuristic for WeightedHeuristic {
+	fn evaluate(&self, grid: &Grid) -> f64 {
+		self.features
+			.iter()
+			.map(|(weight, feature)| weight * feature.evaluate(grid))
+			.sum()
+	}
+}
+
+impl FromStr for WeightedHeuristic {
+	type Err = WeightsError;
+
+	/// one `name = weight` line per feature, empty lines and lines starting with `#` are ignored
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut heuristic = Self::new();
+		for (index, line) in s.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let invalid_line = || WeightsError::InvalidLine(index + 1);
+			let (name, weight) = line.split_once('=').ok_or_else(invalid_line)?;
+			let (name, weight) = (name.trim(), weight.trim());
+			let weight = weight.parse().map_err(|_| invalid_line())?;
+			let feature = Self::feature(name)
+				.ok_or_else(|| WeightsError::UnknownFeature(name.to_string()))?;
+			heuristic.features.push((weight, feature));
+		}
+		Ok(heuristic)
+	}
+}
+
+#[derive(Debug)]
+pub enum WeightsError {
+	Io(io::Error),
+	InvalidLine(usize),
+	UnknownFeature(String),
+}
+
+impl From<io::Error> for WeightsError {
+	fn from(error: io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+impl Display for WeightsError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io(error) => error.fmt(f),
+			Self::InvalidLine(line) => write!(f, "line {line} is not of the form 'name = weight'"),
+			Self::UnknownFeature(name) => write!(f, "unknown feature '{name}'"),
+		}
+	}
+}
+
+impl Error for WeightsError {}
+
+/// pairs of horizontally or vertically adjacent positions
+fn neighbours(grid: &Grid) -> impl Iterator<Item = ((usize, usize), (usize, usize))> {
+	let (width, height) = (grid.width(), grid.height());
+	grid.positions().flat_map(move |(x, y)| {
+		let right = (x + 1 < width).then_some(((x, y), (x + 1, y)));
+		let down = (y + 1 < height).then_some(((x, y), (x, y + 1)));
+		right.into_iter().chain(down)
+	})
+}
+
+/// values of every row and column
+fn lines(grid: &Grid) -> impl Iterator<Item = Vec<Option<usize>>> + '_ {
+	let rows =
+		(0..grid.height()).map(|y| (0..grid.width()).map(|x| grid.get_val((x, y))).collect());
+	let columns =
+		(0..grid.width()).map(|x| (0..grid.height()).map(|y| grid.get_val((x, y))).collect());
+	rows.chain(columns)
+}
+
+/// logarithm of a value, so that features do not depend on the scale of the tiles
+fn rank(value: Option<usize>) -> f64 {
+	match value {
+		Some(value) => (value as f64).log2() + 1.,
+		None => 0.,
+	}
+}
@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+use rand_chacha::ChaCha8Rng;
+
+use super::grid::Grid;
+
+/// state of a game between two turns
+#[derive(Clone)]
+pub struct Snapshot {
+	pub board: Grid,
+	pub score: usize,
+	pub turn_index: usize,
+	/// wether the player chose to keep going after a victory
+	pub keep_going: bool,
+	pub rng: ChaCha8Rng,
+}
+
+///
+/// states a game went through, to go back and forth between them
+///
+#[derive(Clone)]
+pub struct History {
+	past: VecDeque<Snapshot>,
+	future: Vec<Snapshot>,
+	limit: Option<usize>,
+}
+
+impl History {
+	///
+	/// constructor, keeps at most `limit` past states if there is one
+	///
+	pub fn new(limit: Option<usize>) -> Self {
+		Self {
+			past: VecDeque::new(),
+			future: vec![],
+			limit,
+		}
+	}
+
+	///
+	/// record the state preceding a turn, forgets the states that were undone
+	///
+	pub fn push(&mut self, snapshot: Snapshot) {
+		self.future.clear();
+		if !self.records() {
+			return;
+		}
+		self.past.push_back(snapshot);
+		if let Some(limit) = self.limit {
+			while self.past.len() > limit {
+				self.past.pop_front();
+			}
+		}
+	}
+
+	///
+	/// wether states are kept at all, games without history can skip taking them
+	///
+	pub fn records(&self) -> bool {
+		self.limit != Some(0)
+	}
+
+	///
+	/// exchange the current state for the previous one if there is one
+	///
+	pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+		let previous = self.past.pop_back()?;
+		self.future.push(current);
+		Some(previous)
+	}
+
+	///
+	/// exchange the current state for the last undone one if there is one
+	///
+	pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+		let next = self.future.pop()?;
+		self.past.push_back(current);
+		Some(next)
+	}
+
+	///
+	/// get the number of turns that can be undone
+	///
+	pub fn undo_count(&self) -> usize {
+		self.past.len()
+	}
+
+	///
+	/// get the number of turns that can be redone
+	///
+	pub fn redo_count(&self) -> usize {
+		self.future.len()
+	}
+}
@@ -1,19 +1,34 @@
+pub mod bench;
+pub mod bitboard;
+pub mod color_support;
 pub mod controller;
 pub mod game;
 pub mod game_manager;
 pub mod grid;
 pub mod grid_displayer;
-
-pub fn clear_term() {
-	print!("\x1B[2J\x1B[1;1H");
-}
+pub mod heuristic;
+pub mod history;
+pub mod observer;
+pub mod renderer;
+pub mod replay;
+pub mod screen;
+pub mod theme;
+pub mod turn_outcome;
 
 pub mod prelude {
 	pub use super::controller::{
-		Controller, PlayerController, RandomController, SimulatedController,
+		mcts::Budget, ntuple::NTupleNetwork, simulated::Objective, Action, Controller,
+		ExpectimaxController, ExternalController, GreedyController, MctsController, Move,
+		NTupleController, PlayerController, RandomController, SimulatedController,
 	};
-	pub use super::game::GameError;
 	pub use super::game::Rules as GameRules;
+	pub use super::game::{Game, GameError, GameStatus, MergeMode, SpawnTable};
 	pub use super::game_manager::GameManager;
 	pub use super::game_manager::Rules as ManagerRules;
+	pub use super::heuristic::{Heuristic, WeightedHeuristic};
+	pub use super::observer::Observer;
+	pub use super::renderer::{AnsiRenderer, PlainRenderer, Renderer};
+	pub use super::replay::{Playback, Replay};
+	pub use super::theme::Theme;
+	pub use super::turn_outcome::TurnOutcome;
 }
@@ -0,0 +1,29 @@
+use crate::{controller::Move, game::Game, turn_outcome::Spawn};
+
+///
+/// gets notified of what happens in a game played by a [`GameManager`](crate::game_manager::GameManager),
+/// every callback does nothing by default
+///
+pub trait Observer {
+	/// the manager starts playing the game
+	fn game_start(&mut self, _game: &Game) {}
+
+	/// the controller chose a legal move, it is not played yet
+	fn before_move(&mut self, _game: &Game, _movement: Move) {}
+
+	/// a move was played, the game already holds the tiles that spawned after it
+	fn after_move(&mut self, _game: &Game, _movement: Move, _score_delta: usize) {}
+
+	/// a tile spawned after a move, called after [`Observer::after_move`]
+	fn spawn(&mut self, _game: &Game, _spawn: Spawn) {}
+
+	/// the game was lost, the controller stopped playing after a victory, or play stopped on a quit or an error
+	fn game_over(&mut self, _game: &Game) {}
+
+	fn into_box(self) -> Box<dyn Observer>
+	where
+		Self: Sized + 'static,
+	{
+		Box::new(self)
+	}
+}
@@ -0,0 +1,101 @@
+use std::io::{self, Write};
+
+use crate::{game::Game, grid_displayer::GridDisplayer, turn_outcome::TurnOutcome};
+
+///
+/// draws games for a [`GameManager`](crate::game_manager::GameManager)
+///
+pub trait Renderer {
+	/// draw the game, with what happened during the last turn when it is known
+	fn render(
+		&mut self,
+		output: &mut dyn Write,
+		game: &Game,
+		outcome: Option<&TurnOutcome>,
+	) -> io::Result<()>;
+
+	/// show a message under the game, like a victory or a game over
+	fn message(&mut self, output: &mut dyn Write, message: &str) -> io::Result<()> {
+		writeln!(output, "{message}")
+	}
+
+	fn into_box(self) -> Box<dyn Renderer>
+	where
+		Self: Sized + 'static,
+	{
+		Box::new(self)
+	}
+}
+
+///
+/// the line shown above the grid
+///
+pub fn headline(game: &Game) -> String {
+	let score = game.get_score();
+	let turn = game.get_turn_index();
+	let biggest_tile = game.get_board().biggest_value();
+	format!("score: {score:>12} | biggest tile: {biggest_tile:>12} | turn: {turn:>12}")
+}
+
+///
+/// colored boxes drawn with ANSI escape codes, the default renderer
+///
+pub struct AnsiRenderer {
+	grid_displayer: GridDisplayer,
+}
+
+impl AnsiRenderer {
+	pub fn new(grid_displayer: GridDisplayer) -> Self {
+		Self { grid_displayer }
+	}
+}
+
+impl Renderer for AnsiRenderer {
+	fn render(
+		&mut self,
+		output: &mut dyn Write,
+		game: &Game,
+		_outcome: Option<&TurnOutcome>,
+	) -> io::Result<()> {
+		writeln!(output, "{}", headline(game))?;
+		writeln!(output, "{}", self.grid_displayer.display(game.get_board()))
+	}
+}
+
+///
+/// ASCII characters only, without colors
+///
+#[derive(Debug, Default)]
+pub struct PlainRenderer;
+
+impl Renderer for PlainRenderer {
+	fn render(
+		&mut self,
+		output: &mut dyn Write,
+		game: &Game,
+		_outcome: Option<&TurnOutcome>,
+	) -> io::Result<()> {
+		let board = game.get_board();
+		let cell_length = board.biggest_value().to_string().len().max(4) + 2;
+		let separator = format!(
+			"+{}",
+			format!("{}+", "-".repeat(cell_length)).repeat(board.width())
+		);
+		writeln!(output, "{}", headline(game))?;
+		writeln!(output, "{separator}")?;
+		for y in 0..board.height() {
+			let row: String = (0..board.width())
+				.map(|x| {
+					let value = board
+						.get_val((x, y))
+						.map(|value| value.to_string())
+						.unwrap_or_default();
+					format!("{value:^cell_length$}|")
+				})
+				.collect();
+			writeln!(output, "|{row}")?;
+			writeln!(output, "{separator}")?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,282 @@
+use std::{
+	fs::File,
+	io::{self, stdout, BufReader, BufWriter, Read, Write},
+	path::Path,
+	thread,
+	time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use termion::{async_stdin, event::Key, input::TermRead, raw::IntoRawMode};
+
+use crate::{
+	controller::Move,
+	game::{self, Game},
+	grid::Grid,
+	grid_displayer::GridDisplayer,
+	screen::Screen,
+	turn_outcome::{Spawn, TurnOutcome},
+};
+
+/// version of the replay files written by this version of the game
+const VERSION: u32 = 1;
+
+/// most cells of the grid of a replay, bigger grids would not fit in memory or on a terminal
+const MAX_CELLS: usize = 1 << 20;
+
+/// a move and the tiles that spawned after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayTurn {
+	pub movement: Move,
+	pub spawns: Vec<Spawn>,
+}
+
+///
+/// record of a game, enough to watch it again without its rng
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+	version: u32,
+	rules: game::Rules,
+	seed: u64,
+	/// tiles on the grid before the first turn
+	start: Vec<Spawn>,
+	/// score before the first turn, games loaded from a save do not start at 0
+	#[serde(default)]
+	start_score: usize,
+	turns: Vec<ReplayTurn>,
+	/// undone turns, waiting to be redone
+	#[serde(skip)]
+	undone: Vec<ReplayTurn>,
+}
+
+impl Replay {
+	///
+	/// constructor of an empty record starting at the current state of a game
+	///
+	pub fn new(game: &Game) -> Self {
+		let board = game.get_board();
+		let start = board
+			.positions()
+			.filter_map(|position| {
+				let value = board.get_val(position)?;
+				Some(Spawn { position, value })
+			})
+			.collect();
+		Self {
+			version: VERSION,
+			rules: game.get_rules().clone(),
+			seed: game.get_seed(),
+			start,
+			start_score: game.get_score(),
+			turns: vec![],
+			undone: vec![],
+		}
+	}
+
+	/// add a turn to the record
+	pub fn record(&mut self, outcome: &TurnOutcome) {
+		self.undone.clear();
+		self.turns.push(ReplayTurn {
+			movement: outcome.movement,
+			spawns: outcome.spawns.clone(),
+		});
+	}
+
+	/// remove the last turn of the record, for when the game undoes it
+	pub fn undo(&mut self) {
+		if let Some(turn) = self.turns.pop() {
+			self.undone.push(turn);
+		}
+	}
+
+	/// put back the last removed turn, for when the game redoes it
+	pub fn redo(&mut self) {
+		if let Some(turn) = self.undone.pop() {
+			self.turns.push(turn);
+		}
+	}
+
+	pub fn get_rules(&self) -> &game::Rules {
+		&self.rules
+	}
+
+	pub fn get_seed(&self) -> u64 {
+		self.seed
+	}
+
+	pub fn get_turns(&self) -> &[ReplayTurn] {
+		&self.turns
+	}
+
+	///
+	/// write the record to a file as JSON
+	///
+	pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+		let mut file = BufWriter::new(File::create(path)?);
+		serde_json::to_writer(&mut file, self)?;
+		file.flush()
+	}
+
+	///
+	/// read a record written by [`Replay::save`], fails on empty or oversized grids
+	/// and on tiles outside of the grid
+	///
+	pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+		let mut text = String::new();
+		BufReader::new(File::open(path)?).read_to_string(&mut text)?;
+		game::check_version(&text, VERSION, "replay")?;
+		let replay: Self = serde_json::from_str(&text)?;
+		let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+		let (width, height) = (replay.rules.get_width(), replay.rules.get_height());
+		match width.checked_mul(height) {
+			Some(cells) if cells > 0 && cells <= MAX_CELLS => (),
+			_ => {
+				return Err(invalid_data(format!(
+					"the {width}x{height} grid is empty or bigger than {MAX_CELLS} cells"
+				)))
+			}
+		}
+		let spawns = replay
+			.turns
+			.iter()
+			.flat_map(|turn| &turn.spawns)
+			.chain(&replay.start);
+		for spawn in spawns {
+			let (x, y) = spawn.position;
+			if x >= width || y >= height {
+				return Err(invalid_data(format!(
+					"tile at ({x}, {y}) outside of the {width}x{height} grid"
+				)));
+			}
+		}
+		Ok(replay)
+	}
+
+	///
+	/// get the state of the game before the first turn and after every turn
+	///
+	pub fn frames(&self) -> Vec<Frame> {
+		let mut board = Grid::new(self.rules.get_width(), self.rules.get_height());
+		for spawn in &self.start {
+			board.set(spawn.position, Some(spawn.value));
+		}
+		let mut frames = vec![Frame {
+			board,
+			score: self.start_score,
+			movement: None,
+		}];
+		for turn in &self.turns {
+			let previous = frames.last().expect("there is a first frame");
+			let mut board = previous.board.clone();
+			let score = Game::move_board(&mut board, turn.movement, self.rules.get_merge_mode());
+			for spawn in &turn.spawns {
+				board.set(spawn.position, Some(spawn.value));
+			}
+			frames.push(Frame {
+				board,
+				score: previous.score + score,
+				movement: Some(turn.movement),
+			});
+		}
+		frames
+	}
+}
+
+/// state of a replayed game after a turn
+#[derive(Clone)]
+pub struct Frame {
+	pub board: Grid,
+	pub score: usize,
+	/// move that led to this state, none for the start of the game
+	pub movement: Option<Move>,
+}
+
+///
+/// shows the frames of a replay one after the other, space pauses, the left and right arrows
+/// step backward and forward, the up and down arrows change the speed and 'q' quits
+///
+pub struct Playback {
+	frames: Vec<Frame>,
+	grid_displayer: GridDisplayer,
+	delay: Duration,
+}
+
+impl Playback {
+	pub fn new(replay: &Replay, grid_displayer: GridDisplayer) -> Self {
+		Self {
+			frames: replay.frames(),
+			grid_displayer,
+			delay: Duration::from_millis(250),
+		}
+	}
+
+	/// time each frame is shown for while playing
+	pub fn delay(mut self, delay: Duration) -> Self {
+		self.delay = delay;
+		self
+	}
+
+	pub fn run(mut self) -> io::Result<()> {
+		let mut stdout = Screen::new(stdout().into_raw_mode()?).terminal(true);
+		let mut keys = async_stdin().keys();
+		let mut index = 0;
+		let mut paused = false;
+		let mut last_step = Instant::now();
+		self.draw(&mut stdout, index, paused)?;
+		loop {
+			let mut changed = false;
+			for key in keys.by_ref() {
+				match key? {
+					Key::Char('q') => return Ok(()),
+					Key::Char(' ') => paused = !paused,
+					Key::Right if index + 1 < self.frames.len() => {
+						index += 1;
+						paused = true;
+					}
+					Key::Left if index > 0 => {
+						index -= 1;
+						paused = true;
+					}
+					Key::Up => self.delay /= 2,
+					Key::Down => self.delay = (self.delay * 2).max(Duration::from_millis(1)),
+					_ => continue,
+				}
+				changed = true;
+			}
+			if !paused && last_step.elapsed() >= self.delay {
+				last_step = Instant::now();
+				if index + 1 < self.frames.len() {
+					index += 1;
+					changed = true;
+				}
+			}
+			if changed {
+				self.draw(&mut stdout, index, paused)?;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+	}
+
+	fn draw(&self, stdout: &mut impl Write, index: usize, paused: bool) -> io::Result<()> {
+		let frame = &self.frames[index];
+		let movement = frame
+			.movement
+			.map(|movement| movement.to_string())
+			.unwrap_or_default();
+		let state = if paused { "paused" } else { "playing" };
+		let headline = format!(
+			"score: {:>12} | turn: {:>6}/{} | move: {movement:>5} | {state}",
+			frame.score,
+			index,
+			self.frames.len() - 1
+		);
+		let grid = self.grid_displayer.display(&frame.board);
+		let help = format!(
+			"space: pause | ←/→: step | ↑/↓: speed ({}ms) | q: quit",
+			self.delay.as_millis()
+		);
+		write!(stdout, "{headline}\n{grid}\n{help}\n")?;
+		stdout.flush()
+	}
+}
@@ -0,0 +1,360 @@
+use std::{
+	io::{self, stdout, Write},
+	panic,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Once,
+	},
+};
+
+use termion::{clear, cursor, screen};
+
+/// screens currently shown on the terminal, it is restored on panics while there are any
+static ACTIVE_SCREENS: AtomicUsize = AtomicUsize::new(0);
+static PANIC_HOOK: Once = Once::new();
+
+///
+/// draws frames on the alternate screen of the terminal with the cursor hidden, what is written
+/// between two flushes is a frame and only the cells that changed since the previous one are
+/// written again, the terminal is restored with the last frame printed on it when dropped,
+/// and without it on panics, programs handling signals restore it with [`restore_terminal`]
+///
+pub struct Screen<W: Write> {
+	output: W,
+	/// text of the frame being written
+	frame: Vec<u8>,
+	/// text of the last frame drawn
+	last_frame: String,
+	/// cells of the last frame drawn, by line
+	cells: Vec<Vec<Cell>>,
+	entered: bool,
+	terminal: bool,
+}
+
+impl<W: Write> Screen<W> {
+	pub fn new(output: W) -> Self {
+		Self {
+			output,
+			frame: vec![],
+			last_frame: String::new(),
+			cells: vec![],
+			entered: false,
+			terminal: false,
+		}
+	}
+
+	///
+	/// wether the output is the terminal of the standard output, it is then restored
+	/// if the program panics while the screen is shown
+	///
+	pub fn terminal(mut self, terminal: bool) -> Self {
+		self.terminal = terminal;
+		self
+	}
+
+	fn enter(&mut self) -> io::Result<()> {
+		if self.terminal {
+			self.restore_on_panic();
+		}
+		self.entered = true;
+		write!(
+			self.output,
+			"{}{}{}",
+			screen::ToAlternateScreen,
+			cursor::Hide,
+			clear::All
+		)
+	}
+
+	fn restore_on_panic(&self) {
+		PANIC_HOOK.call_once(|| {
+			let hook = panic::take_hook();
+			panic::set_hook(Box::new(move |info| {
+				// the message would be lost on the alternate screen
+				restore_terminal();
+				hook(info);
+			}));
+		});
+		ACTIVE_SCREENS.fetch_add(1, Ordering::SeqCst);
+	}
+
+	fn leave(&mut self) -> io::Result<()> {
+		if self.terminal {
+			ACTIVE_SCREENS.fetch_sub(1, Ordering::SeqCst);
+		}
+		self.entered = false;
+		// raw mode does not return to the start of the line on new lines
+		let last_frame = self.last_frame.replace('\r', "").replace('\n', "\r\n");
+		write!(
+			self.output,
+			"\x1B[0m{}{}{last_frame}",
+			cursor::Show,
+			screen::ToMainScreen
+		)?;
+		self.output.flush()
+	}
+
+	/// write the cells of the frame that are not already on the screen
+	fn draw(&mut self) -> io::Result<()> {
+		let text = String::from_utf8_lossy(&self.frame).into_owned();
+		self.frame.clear();
+		let cells = parse(&text);
+		let mut cursor = None;
+		let mut style = None;
+		for (y, line) in cells.iter().enumerate() {
+			let previous_line = self.cells.get(y).map(Vec::as_slice).unwrap_or_default();
+			for (x, cell) in line.iter().enumerate() {
+				if previous_line.get(x) == Some(cell) {
+					continue;
+				}
+				if cursor != Some((x, y)) {
+					write!(self.output, "{}", goto(x, y))?;
+				}
+				if style.as_ref() != Some(&cell.style) {
+					write!(self.output, "{}", cell.style)?;
+					style = Some(cell.style.clone());
+				}
+				write!(self.output, "{}", cell.symbol)?;
+				cursor = Some((x + 1, y));
+			}
+			if previous_line.len() > line.len() {
+				write!(
+					self.output,
+					"{}\x1B[0m{}",
+					goto(line.len(), y),
+					clear::UntilNewline
+				)?;
+				style = Some(Style::default());
+			}
+		}
+		for y in cells.len()..self.cells.len() {
+			write!(self.output, "{}\x1B[0m{}", goto(0, y), clear::CurrentLine)?;
+		}
+		write!(self.output, "\x1B[0m")?;
+		self.cells = cells;
+		self.last_frame = text;
+		Ok(())
+	}
+}
+
+impl<W: Write> Write for Screen<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.frame.extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	/// draw the frame written since the last flush
+	fn flush(&mut self) -> io::Result<()> {
+		if !self.frame.is_empty() {
+			if !self.entered {
+				self.enter()?;
+			}
+			self.draw()?;
+		}
+		self.output.flush()
+	}
+}
+
+impl<W: Write> Drop for Screen<W> {
+	fn drop(&mut self) {
+		if self.entered {
+			let _ = self.leave();
+		}
+	}
+}
+
+///
+/// leave the alternate screen of the standard output if a screen is shown on it,
+/// for programs exiting without dropping the screen like on signals
+///
+pub fn restore_terminal() {
+	if ACTIVE_SCREENS.load(Ordering::SeqCst) > 0 {
+		let mut stdout = stdout();
+		let _ = write!(stdout, "\x1B[0m{}{}", cursor::Show, screen::ToMainScreen);
+		let _ = stdout.flush();
+	}
+}
+
+/// colors and attributes of a cell, as parameters of a select graphic rendition escape code
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Style {
+	foreground: Option<String>,
+	background: Option<String>,
+	attributes: Vec<String>,
+}
+
+impl Style {
+	fn apply(&mut self, parameters: &str) {
+		let mut parameters = parameters.split(';');
+		while let Some(parameter) = parameters.next() {
+			let code: usize = parameter.parse().unwrap_or(0);
+			match code {
+				0 => *self = Self::default(),
+				30..=37 | 90..=97 => self.foreground = Some(parameter.to_string()),
+				40..=47 | 100..=107 => self.background = Some(parameter.to_string()),
+				39 => self.foreground = None,
+				49 => self.background = None,
+				38 | 48 => {
+					// 5 and a palette index or 2 and red, green and blue
+					let mode = parameters.next().unwrap_or_default();
+					let length = if mode == "2" { 3 } else { 1 };
+					let color = [parameter, mode]
+						.into_iter()
+						.chain(parameters.by_ref().take(length))
+						.collect::<Vec<_>>()
+						.join(";");
+					if code == 38 {
+						self.foreground = Some(color);
+					} else {
+						self.background = Some(color);
+					}
+				}
+				_ => self.attributes.push(parameter.to_string()),
+			}
+		}
+	}
+}
+
+impl std::fmt::Display for Style {
+	/// resets the style before setting it, so it does not depend on the previous one
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "\x1B[0")?;
+		for parameter in self
+			.attributes
+			.iter()
+			.chain(&self.foreground)
+			.chain(&self.background)
+		{
+			write!(f, ";{parameter}")?;
+		}
+		write!(f, "m")
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cell {
+	symbol: char,
+	style: Style,
+}
+
+/// cells of a text by line, escape codes other than colors and attributes are left out
+fn parse(text: &str) -> Vec<Vec<Cell>> {
+	let mut lines = vec![vec![]];
+	let mut style = Style::default();
+	let mut chars = text.chars();
+	while let Some(symbol) = chars.next() {
+		match symbol {
+			'\x1B' => {
+				if chars.next() != Some('[') {
+					continue;
+				}
+				let mut parameters = String::new();
+				for symbol in chars.by_ref() {
+					if ('@'..='~').contains(&symbol) {
+						if symbol == 'm' {
+							style.apply(&parameters);
+						}
+						break;
+					}
+					parameters.push(symbol);
+				}
+			}
+			'\n' => lines.push(vec![]),
+			symbol if symbol.is_control() => (),
+			symbol => lines.last_mut().expect("there is a first line").push(Cell {
+				symbol,
+				style: style.clone(),
+			}),
+		}
+	}
+	if lines.last().is_some_and(Vec::is_empty) {
+		lines.pop();
+	}
+	lines
+}
+
+/// escape code moving the cursor to a cell, counted from 0
+fn goto(x: usize, y: usize) -> cursor::Goto {
+	cursor::Goto(x as u16 + 1, y as u16 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+
+	use termion::{clear, cursor, screen};
+
+	use super::{goto, parse, Screen};
+
+	/// draw two frames, get what was written for the second one
+	fn redraw(first: &str, second: &str) -> String {
+		let mut output = vec![];
+		let mut terminal = Screen::new(&mut output);
+		write!(terminal, "{first}").expect("vectors can be written to");
+		terminal.flush().expect("vectors can be written to");
+		terminal.output.clear();
+		write!(terminal, "{second}").expect("vectors can be written to");
+		terminal.flush().expect("vectors can be written to");
+		String::from_utf8(terminal.output.clone()).expect("the frames are text")
+	}
+
+	#[test]
+	fn only_changed_cells_are_written() {
+		let written = redraw("ab\ncd\n", "ab\nxd\n");
+		assert_eq!(written, format!("{}\x1B[0mx\x1B[0m", goto(0, 1)));
+	}
+
+	#[test]
+	fn shortened_lines_are_cleared() {
+		let written = redraw("abc\n", "a\n");
+		let expected = format!("{}\x1B[0m{}\x1B[0m", goto(1, 0), clear::UntilNewline);
+		assert_eq!(written, expected);
+	}
+
+	#[test]
+	fn removed_lines_are_cleared() {
+		let written = redraw("a\nb\n", "a\n");
+		let expected = format!("{}\x1B[0m{}\x1B[0m", goto(0, 1), clear::CurrentLine);
+		assert_eq!(written, expected);
+	}
+
+	#[test]
+	fn styles_carry_over_cells_and_lines() {
+		let cells = parse("\x1B[31;1ma\nb\x1B[39mc");
+		assert_eq!(cells[0][0].style.to_string(), "\x1B[0;1;31m");
+		assert_eq!(cells[1][0].style, cells[0][0].style);
+		assert_eq!(cells[1][1].style.to_string(), "\x1B[0;1m");
+
+		// a cell is written with its whole style, and again when only its style changes
+		let written = redraw("\x1B[31mab", "\x1B[31mac");
+		assert_eq!(written, format!("{}\x1B[0;31mc\x1B[0m", goto(1, 0)));
+		let written = redraw("\x1B[31ma", "\x1B[32ma");
+		assert_eq!(written, format!("{}\x1B[0;32ma\x1B[0m", goto(0, 0)));
+	}
+
+	#[test]
+	fn the_last_frame_is_written_on_drop() {
+		let mut output = vec![];
+		let mut terminal = Screen::new(&mut output);
+		write!(terminal, "a\nb\n").expect("vectors can be written to");
+		terminal.flush().expect("vectors can be written to");
+		drop(terminal);
+		let written = String::from_utf8(output).expect("the frames are text");
+		let entered = format!(
+			"{}{}{}",
+			screen::ToAlternateScreen,
+			cursor::Hide,
+			clear::All
+		);
+		let left = format!("\x1B[0m{}{}a\r\nb\r\n", cursor::Show, screen::ToMainScreen);
+		assert!(written.starts_with(&entered), "{written:?}");
+		assert!(written.ends_with(&left), "{written:?}");
+	}
+
+	#[test]
+	fn nothing_is_written_without_frames() {
+		let mut output = vec![];
+		drop(Screen::new(&mut output));
+		assert!(output.is_empty());
+	}
+}
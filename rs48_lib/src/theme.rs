@@ -0,0 +1,278 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, fs, io, path::Path, str::FromStr};
+
+use serde::Deserialize;
+
+/// colors of a tile, as red, green and blue components
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileColors {
+	pub background: (u8, u8, u8),
+	pub foreground: (u8, u8, u8),
+}
+
+///
+/// colors of the tiles for each value
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+	name: String,
+	tiles: BTreeMap<usize, TileColors>,
+	/// colors of values without their own
+	default: TileColors,
+}
+
+impl Theme {
+	/// names of the built-in themes
+	pub const BUILT_IN: [&'static str; 4] = ["classic", "dark", "high-contrast", "colorblind"];
+
+	///
+	/// get a built-in theme from its name
+	///
+	pub fn built_in(name: &str) -> Option<Self> {
+		match name {
+			"classic" => Some(Self::classic()),
+			"dark" => Some(Self::dark()),
+			"high-contrast" => Some(Self::high_contrast()),
+			"colorblind" => Some(Self::colorblind()),
+			_ => None,
+		}
+	}
+
+	///
+	/// beige and orange tiles of the original game
+	///
+	pub fn classic() -> Self {
+		let (dark, light) = (0x776e65, 0xf9f6f2);
+		Self::from_palette(
+			"classic",
+			&[
+				(0xf3ede5, dark),
+				(0xeee4da, dark),
+				(0xede0c8, dark),
+				(0xf2b179, light),
+				(0xf59563, light),
+				(0xf67c5f, light),
+				(0xf65e3b, light),
+				(0xedcf72, light),
+				(0xedcc61, light),
+				(0xedc850, light),
+				(0xedc53f, light),
+				(0xedc22e, light),
+			],
+			(0x3c3a32, light),
+		)
+	}
+
+	///
+	/// deep colors getting warmer as values grow, for dark terminals
+	///
+	pub fn dark() -> Self {
+		let light = 0xeceff4;
+		Self::from_palette(
+			"dark",
+			&[
+				(0x2e3440, light),
+				(0x3b4252, light),
+				(0x434c5e, light),
+				(0x4c566a, light),
+				(0x5e4b8b, light),
+				(0x7b3f8c, light),
+				(0x9c3587, light),
+				(0xb8336a, light),
+				(0xcf4a4a, light),
+				(0xd8692e, light),
+				(0xe0891a, light),
+				(0xe8ad12, 0x1d1f21),
+			],
+			(0xf0d000, 0x1d1f21),
+		)
+	}
+
+	///
+	/// saturated colors alternating between dark and light backgrounds
+	///
+	pub fn high_contrast() -> Self {
+		let (black, white) = (0x000000, 0xffffff);
+		Self::from_palette(
+			"high-contrast",
+			&[
+				(white, black),
+				(0x000080, white),
+				(0xffff00, black),
+				(0x800000, white),
+				(0x00ffff, black),
+				(0x008000, white),
+				(0xff00ff, black),
+				(0x0000ff, white),
+				(0x00ff00, black),
+				(0xff0000, white),
+				(0xffa500, black),
+				(black, white),
+			],
+			(0x808080, black),
+		)
+	}
+
+	///
+	/// two greys then the Okabe-Ito palette, followed by dark colors of Paul Tol's palettes
+	/// for the biggest tiles, told apart with the most common color blindnesses
+	///
+	pub fn colorblind() -> Self {
+		let (black, white) = (0x000000, 0xffffff);
+		Self::from_palette(
+			"colorblind",
+			&[
+				(0xdddddd, black),
+				(0x999999, black),
+				(0x56b4e9, black),
+				(0xe69f00, black),
+				(0x009e73, white),
+				(0xf0e442, black),
+				(0x0072b2, white),
+				(0xd55e00, white),
+				(0xcc79a7, black),
+				(0x332288, white),
+				(0x882255, white),
+				(0x117733, white),
+			],
+			(black, white),
+		)
+	}
+
+	///
+	/// read a theme from a TOML file, see [`Theme::from_str`]
+	///
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, ThemeError> {
+		fs::read_to_string(path)?.parse()
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// get the colors of a tile
+	pub fn colors(&self, value: usize) -> TileColors {
+		self.tiles.get(&value).copied().unwrap_or(self.default)
+	}
+
+	/// pairs of background and foreground colors for the powers of two from 1 onwards
+	fn from_palette(name: &str, palette: &[(u32, u32)], default: (u32, u32)) -> Self {
+		let colors = |(background, foreground)| TileColors {
+			background: rgb(background),
+			foreground: rgb(foreground),
+		};
+		let tiles = palette
+			.iter()
+			.enumerate()
+			.map(|(exponent, &pair)| (1 << exponent, colors(pair)))
+			.collect();
+		Self {
+			name: name.to_string(),
+			tiles,
+			default: colors(default),
+		}
+	}
+}
+
+impl FromStr for Theme {
+	type Err = ThemeError;
+
+	///
+	/// a TOML document with the colors of each value in a `tiles` table, and the colors
+	/// of the other values in `default`, colors are written like `#eee4da`
+	///
+	/// ```toml
+	/// name = "example"
+	/// default = { background = "#3c3a32", foreground = "#f9f6f2" }
+	///
+	/// [tiles]
+	/// 2 = { background = "#eee4da", foreground = "#776e65" }
+	/// 4 = { background = "#ede0c8", foreground = "#776e65" }
+	/// ```
+	///
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let file: ThemeFile = toml::from_str(s)?;
+		let tiles = file
+			.tiles
+			.into_iter()
+			.map(|(value, colors)| {
+				let value = value
+					.parse()
+					.map_err(|_| ThemeError::InvalidValue(value.clone()))?;
+				Ok((value, colors.parse()?))
+			})
+			.collect::<Result<_, ThemeError>>()?;
+		Ok(Self {
+			name: file.name.unwrap_or_else(|| "custom".to_string()),
+			tiles,
+			default: file.default.parse()?,
+		})
+	}
+}
+
+#[derive(Deserialize)]
+struct ThemeFile {
+	name: Option<String>,
+	default: ColorsEntry,
+	#[serde(default)]
+	tiles: BTreeMap<String, ColorsEntry>,
+}
+
+#[derive(Deserialize)]
+struct ColorsEntry {
+	background: String,
+	foreground: String,
+}
+
+impl ColorsEntry {
+	fn parse(&self) -> Result<TileColors, ThemeError> {
+		let parse = |color: &str| {
+			color
+				.strip_prefix('#')
+				.filter(|hex| hex.len() == 6)
+				.and_then(|hex| u32::from_str_radix(hex, 16).ok())
+				.map(rgb)
+				.ok_or_else(|| ThemeError::InvalidColor(color.to_string()))
+		};
+		Ok(TileColors {
+			background: parse(&self.background)?,
+			foreground: parse(&self.foreground)?,
+		})
+	}
+}
+
+fn rgb(color: u32) -> (u8, u8, u8) {
+	((color >> 16) as u8, (color >> 8) as u8, color as u8)
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+	Io(io::Error),
+	Toml(toml::de::Error),
+	InvalidColor(String),
+	InvalidValue(String),
+}
+
+impl From<io::Error> for ThemeError {
+	fn from(error: io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+impl From<toml::de::Error> for ThemeError {
+	fn from(error: toml::de::Error) -> Self {
+		Self::Toml(error)
+	}
+}
+
+impl Display for ThemeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io(error) => error.fmt(f),
+			Self::Toml(error) => error.fmt(f),
+			Self::InvalidColor(color) => write!(f, "'{color}' is not a color like '#eee4da'"),
+			Self::InvalidValue(value) => write!(f, "'{value}' is not a tile value"),
+		}
+	}
+}
+
+impl Error for ThemeError {}
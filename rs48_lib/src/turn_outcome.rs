@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+use super::{controller::Move, game::MergeMode, grid::Grid};
+
+/// a tile that changed position during a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slide {
+	pub from: (usize, usize),
+	pub to: (usize, usize),
+}
+
+/// two tiles that became one during a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Merge {
+	/// positions of the two tiles before the move
+	pub sources: [(usize, usize); 2],
+	/// position of the resulting tile
+	pub position: (usize, usize),
+	pub value: usize,
+	pub score: usize,
+}
+
+/// a tile that appeared after a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spawn {
+	pub position: (usize, usize),
+	pub value: usize,
+}
+
+///
+/// everything that happened during a turn
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnOutcome {
+	pub movement: Move,
+	pub score: usize,
+	pub slides: Vec<Slide>,
+	pub merges: Vec<Merge>,
+	pub spawns: Vec<Spawn>,
+}
+
+/// tile placed in a line while describing a move
+struct Placed {
+	value: usize,
+	source: (usize, usize),
+	merged: bool,
+}
+
+///
+/// get the slides and merges a move would do on a grid, without performing it
+///
+pub fn describe_move(
+	grid: &Grid,
+	movement: Move,
+	merge_mode: MergeMode,
+) -> (Vec<Slide>, Vec<Merge>) {
+	let (width, height) = (grid.width(), grid.height());
+	let (line_count, line_length) = match movement {
+		Move::LEFT | Move::RIGHT => (height, width),
+		Move::UP | Move::DOWN => (width, height),
+	};
+	// position of a cell in a line, lines start from the side the tiles move towards
+	let position = |line: usize, index: usize| match movement {
+		Move::LEFT => (index, line),
+		Move::RIGHT => (width - 1 - index, line),
+		Move::UP => (line, index),
+		Move::DOWN => (line, height - 1 - index),
+	};
+
+	let mut slides = vec![];
+	let mut merges = vec![];
+	let mut placed: Vec<Placed> = vec![];
+	for line in 0..line_count {
+		placed.clear();
+		for index in 0..line_length {
+			let source = position(line, index);
+			let value = match grid.get_val(source) {
+				Some(value) => value,
+				None => continue,
+			};
+			let length = placed.len();
+			match placed.last_mut() {
+				Some(last)
+					if last.value == value
+						&& (merge_mode == MergeMode::Cascading || !last.merged) =>
+				{
+					let to = position(line, length - 1);
+					slides.push(Slide { from: source, to });
+					merges.push(Merge {
+						sources: [last.source, source],
+						position: to,
+						value: value * 2,
+						score: value * 2,
+					});
+					last.value = value * 2;
+					last.merged = true;
+				}
+				_ => {
+					let to = position(line, length);
+					if to != source {
+						slides.push(Slide { from: source, to });
+					}
+					placed.push(Placed {
+						value,
+						source,
+						merged: false,
+					});
+				}
+			}
+		}
+	}
+	(slides, merges)
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{Rng, SeedableRng};
+	use rand_chacha::ChaCha8Rng;
+
+	use super::describe_move;
+	use crate::{
+		controller::Move,
+		game::{Game, MergeMode},
+		grid::Grid,
+	};
+
+	#[test]
+	fn descriptions_match_the_moves() {
+		let mut rng = ChaCha8Rng::seed_from_u64(0);
+		for _ in 0..10_000 {
+			let (width, height) = (rng.gen_range(1..7), rng.gen_range(1..7));
+			let mut grid = Grid::new(width, height);
+			grid.fill_randomly(&mut rng, 1, 3);
+			for merge_mode in [MergeMode::Standard, MergeMode::Cascading] {
+				for movement in Move::all() {
+					let (slides, merges) = describe_move(&grid, movement, merge_mode);
+					// tiles go where they slide, then merges replace the values they meet at
+					let mut described = Grid::new(width, height);
+					for position in grid.positions() {
+						let to = slides
+							.iter()
+							.find(|slide| slide.from == position)
+							.map_or(position, |slide| slide.to);
+						if let Some(value) = grid.get_val(position) {
+							described.set(to, Some(value));
+						}
+					}
+					for merge in &merges {
+						described.set(merge.position, Some(merge.value));
+					}
+
+					let mut moved = grid.clone();
+					let score = Game::move_board(&mut moved, movement, merge_mode);
+					let context = format!("{movement:?} in {merge_mode:?} mode");
+					assert_eq!(described.values(), moved.values(), "tiles after {context}");
+					let merge_score: usize = merges.iter().map(|merge| merge.score).sum();
+					assert_eq!(merge_score, score, "score of {context}");
+				}
+			}
+		}
+	}
+}